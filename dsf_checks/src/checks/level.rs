@@ -0,0 +1,144 @@
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use dsf_core::levels::{is_level_text_file, load_level_save, validate, RESERVED_LEVEL_NAMES};
+use dsf_core::resources::{MovementConfig, TileDefinitions};
+use dsf_core::sim::Simulation;
+
+use crate::solver::{Solution, Solver};
+
+/// The outcome of checking a single level file.
+#[derive(Debug)]
+pub struct LevelReport {
+    pub path: PathBuf,
+    /// Structural problems with the level. If there are any, the solver is not run.
+    pub problems: Vec<String>,
+    /// The verdict of the solver. None if the solver was not run.
+    pub solution: Option<Solution>,
+}
+
+impl LevelReport {
+    /// A level passes if it has no structural problems and the solver proved that it can be won.
+    pub fn passed(&self) -> bool {
+        self.problems.is_empty()
+            && self
+                .solution
+                .as_ref()
+                .map(|solution| solution.is_solved())
+                .unwrap_or(false)
+    }
+}
+
+impl fmt::Display for LevelReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| self.path.to_string_lossy());
+        writeln!(
+            f,
+            "{} {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            name
+        )?;
+        for problem in &self.problems {
+            writeln!(f, "    - {}", problem)?;
+        }
+        match &self.solution {
+            Some(Solution::Solved(actions)) => {
+                writeln!(
+                    f,
+                    "    solvable in {} actions: {:?}",
+                    actions.len(),
+                    actions
+                )
+            }
+            Some(Solution::NoSolutionFound { explored_states }) => writeln!(
+                f,
+                "    - no solution found: none of the {} states the solver can reach win the level",
+                explored_states
+            ),
+            Some(Solution::NeverSettled) => writeln!(
+                f,
+                "    - the player falls forever from the start and never comes to rest"
+            ),
+            Some(Solution::Inconclusive { explored_states }) => writeln!(
+                f,
+                "    - inconclusive: gave up after exploring {} states",
+                explored_states
+            ),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns all level files at the given path. If the path is a file, that is the only level.
+/// If the path is a directory, all level files directly inside it are returned, sorted by name.
+/// Level files are `.ron` files and files in the text format. Files with a reserved name, such as
+/// the editor's auto save, are not levels and are skipped.
+pub fn find_level_files(path: &PathBuf) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.clone()];
+    }
+    let mut files = fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.is_file()
+                        && (is_level_text_file(path)
+                            || path.extension().map(|ext| ext == "ron").unwrap_or(false))
+                        && !is_reserved(path)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_else(|error| {
+            error!("Failed to read directory {:?}: {:?}", path, error);
+            Vec::new()
+        });
+    files.sort();
+    files
+}
+
+/// Returns true if the file has one of the names that the editor reserves for its own files.
+fn is_reserved(path: &PathBuf) -> bool {
+    path.file_stem()
+        .map(|stem| RESERVED_LEVEL_NAMES.iter().any(|name| stem == *name))
+        .unwrap_or(false)
+}
+
+/// Loads the level from the given file, checks it for structural problems and then tries to solve
+/// it.
+pub fn check_level(
+    path: &PathBuf,
+    tile_defs: &TileDefinitions,
+    config: &MovementConfig,
+    solver: &Solver,
+) -> LevelReport {
+    let mut report = LevelReport {
+        path: path.clone(),
+        problems: Vec::new(),
+        solution: None,
+    };
+    let level = match load_level_save(path) {
+        Ok(level) => level,
+        Err(error) => {
+            report
+                .problems
+                .push(format!("failed to load level: {:?}", error));
+            return report;
+        }
+    };
+    report.problems = validate(&level, tile_defs)
+        .iter()
+        .map(|issue| issue.to_string())
+        .collect();
+    if report.problems.is_empty() {
+        report.solution =
+            Simulation::new(level, tile_defs.clone(), config.clone()).map(|sim| solver.solve(sim));
+    }
+    report
+}
@@ -0,0 +1,144 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+use crate::import::tiled::{TiledMap, TiledObject, TiledTileset};
+
+/// A map in Tiled's JSON format. Only the fields that the importer uses are read.
+#[derive(Debug, Deserialize)]
+struct JsonMap {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    #[serde(default)]
+    infinite: bool,
+    #[serde(default)]
+    layers: Vec<JsonLayer>,
+    #[serde(default)]
+    tilesets: Vec<JsonTileset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonTileset {
+    firstgid: u32,
+    /// Only embedded tilesets have a name.
+    #[serde(default)]
+    name: Option<String>,
+    /// Only tilesets that are stored in their own file have a source.
+    #[serde(default)]
+    source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum JsonLayer {
+    #[serde(rename = "tilelayer")]
+    TileLayer {
+        #[serde(default)]
+        data: Option<JsonLayerData>,
+    },
+    #[serde(rename = "objectgroup")]
+    ObjectGroup {
+        #[serde(default)]
+        objects: Vec<JsonObject>,
+    },
+    #[serde(rename = "group")]
+    Group {
+        #[serde(default)]
+        layers: Vec<JsonLayer>,
+    },
+    /// Image layers and any layer types that future versions of Tiled may add.
+    #[serde(other)]
+    Other,
+}
+
+/// Tile layers store their tile ids either as a plain array or as a base64 string.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonLayerData {
+    Ids(Vec<u32>),
+    /// Encoded layers are not supported, so their contents are skipped.
+    Encoded(IgnoredAny),
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonObject {
+    #[serde(default)]
+    x: f32,
+    #[serde(default)]
+    y: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    gid: Option<u32>,
+    #[serde(default, rename = "type")]
+    object_type: String,
+    /// Tiled 1.9 renamed the object type to class.
+    #[serde(default)]
+    class: String,
+}
+
+/// Reads a map in Tiled's JSON format.
+pub(crate) fn parse_json(text: &str) -> Result<TiledMap, String> {
+    let json_map: JsonMap =
+        serde_json::from_str(text).map_err(|error| format!("Invalid Tiled map: {}", error))?;
+    if json_map.infinite {
+        return Err("Infinite maps are not supported.".to_string());
+    }
+    let mut map = TiledMap {
+        width: json_map.width,
+        height: json_map.height,
+        tile_width: json_map.tilewidth,
+        tile_height: json_map.tileheight,
+        tilesets: json_map
+            .tilesets
+            .iter()
+            .map(|tileset| {
+                TiledTileset::new(
+                    tileset.firstgid,
+                    tileset.name.as_deref(),
+                    tileset.source.as_deref(),
+                )
+            })
+            .collect(),
+        ..TiledMap::default()
+    };
+    add_layers(&mut map, json_map.layers)?;
+    Ok(map)
+}
+
+/// Adds the given layers to the map. Layers inside groups are added as well.
+fn add_layers(map: &mut TiledMap, layers: Vec<JsonLayer>) -> Result<(), String> {
+    for layer in layers {
+        match layer {
+            JsonLayer::TileLayer {
+                data: Some(JsonLayerData::Ids(ids)),
+            } => map.tile_layers.push(ids),
+            JsonLayer::TileLayer {
+                data: Some(JsonLayerData::Encoded(_)),
+            } => {
+                return Err(
+                    "Encoded tile layers are not supported. Set the layer format to CSV."
+                        .to_string(),
+                )
+            }
+            JsonLayer::TileLayer { data: None } | JsonLayer::Other => (),
+            JsonLayer::ObjectGroup { objects } => {
+                map.objects
+                    .extend(objects.into_iter().map(|object| TiledObject {
+                        x: object.x,
+                        y: object.y,
+                        height: object.height,
+                        gid: object.gid,
+                        object_type: if object.object_type.is_empty() {
+                            object.class
+                        } else {
+                            object.object_type
+                        },
+                    }))
+            }
+            JsonLayer::Group { layers } => add_layers(map, layers)?,
+        }
+    }
+    Ok(())
+}
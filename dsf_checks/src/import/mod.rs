@@ -0,0 +1,5 @@
+mod json;
+mod tiled;
+mod tmx;
+
+pub use self::tiled::*;
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use dsf_core::components::Pos;
+use dsf_core::levels::LevelSave;
+use dsf_core::resources::WorldBounds;
+
+use crate::import::json::parse_json;
+use crate::import::tmx::parse_tmx;
+
+/// Tiled stores whether a tile is flipped or rotated in the highest bits of its global tile id.
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+/// Tells the importer which tile definition to use for each tile and object in a Tiled map.
+/// Is stored as a RON file, such as assets/world/tiled_mapping.ron.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct TiledMapping {
+    /// Maps tileset names to a table of tile ids and their tile definition keys. The tile ids are
+    /// the ids that Tiled shows in the tileset, the first tile of a tileset has id 0. A tileset
+    /// that is stored in its own file is named after that file, without the extension.
+    pub tiles: HashMap<String, HashMap<u32, String>>,
+    /// Maps object types to tile definition keys. Newer versions of Tiled call the object type
+    /// its class.
+    pub objects: HashMap<String, String>,
+}
+
+/// A part of the Tiled map that could not be imported. The rest of the map is imported regardless.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportIssue {
+    /// The mapping file does not say which tile definition to use for this tile of this tileset.
+    /// The tileset is empty if the tile does not belong to any tileset of the map.
+    UnmappedTile { tileset: String, id: u32, pos: Pos },
+    /// The mapping file does not say which tile definition to use for this object type, and the
+    /// object is not a tile object with a mapped tile id either.
+    UnmappedObject { object_type: String, pos: Pos },
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportIssue::UnmappedTile { tileset, id, pos } => write!(
+                f,
+                "tile id {} of tileset {:?} at {:?} has no mapping",
+                id, tileset, pos
+            ),
+            ImportIssue::UnmappedObject { object_type, pos } => {
+                write!(f, "object {:?} at {:?} has no mapping", object_type, pos)
+            }
+        }
+    }
+}
+
+/// The parts of a Tiled map that the importer uses. Both the TMX and the JSON format are read into
+/// this.
+#[derive(Debug, Default)]
+pub(crate) struct TiledMap {
+    /// The width of the map, in tiles.
+    pub width: u32,
+    /// The height of the map, in tiles.
+    pub height: u32,
+    /// The width of a tile, in pixels. Objects are positioned in pixels.
+    pub tile_width: u32,
+    /// The height of a tile, in pixels.
+    pub tile_height: u32,
+    pub tilesets: Vec<TiledTileset>,
+    /// The global tile ids of every tile layer, row by row, starting at the top left.
+    /// Empty cells have id 0.
+    pub tile_layers: Vec<Vec<u32>>,
+    pub objects: Vec<TiledObject>,
+}
+
+/// A tileset used by a Tiled map. Global tile ids are numbered across all tilesets of the map.
+#[derive(Debug, Default)]
+pub(crate) struct TiledTileset {
+    /// The global tile id of the first tile in this tileset.
+    pub first_gid: u32,
+    /// The name under which the tileset's tiles are found in the mapping.
+    pub name: String,
+}
+
+impl TiledTileset {
+    /// Embedded tilesets have a name. Tilesets that are stored in their own file only have a
+    /// source in the map, so they are named after that file instead.
+    pub fn new(first_gid: u32, name: Option<&str>, source: Option<&str>) -> Self {
+        let name = match (name, source) {
+            (Some(name), _) if !name.is_empty() => name.to_string(),
+            (_, Some(source)) => Path::new(source)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        TiledTileset { first_gid, name }
+    }
+}
+
+/// An object from one of the object layers of a Tiled map.
+#[derive(Debug, Default)]
+pub(crate) struct TiledObject {
+    /// Horizontal position of the left edge, in pixels.
+    pub x: f32,
+    /// Vertical position, in pixels, counting downwards from the top of the map. This is the
+    /// bottom edge for tile objects and the top edge for all other objects.
+    pub y: f32,
+    pub height: f32,
+    /// The global tile id, if this is a tile object.
+    pub gid: Option<u32>,
+    /// The type, or class, of the object. Empty if it has none.
+    pub object_type: String,
+}
+
+/// Reads the Tiled map in the given file and converts it into a level. Files with the `.json`
+/// extension are read as JSON maps, all others as TMX maps.
+///
+/// Every tile layer and every object layer is imported, in order. The world bounds match the size
+/// of the map. Tiles and objects that have no mapping are left out and reported as issues.
+/// Returns an error if the file cannot be read or uses a feature the importer does not support.
+pub fn import_tiled_map(
+    path: &Path,
+    mapping: &TiledMapping,
+) -> Result<(LevelSave, Vec<ImportIssue>), String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{:?}", error))?;
+    let is_json = path.extension().map(|ext| ext == "json").unwrap_or(false);
+    let map = if is_json {
+        parse_json(&text)?
+    } else {
+        parse_tmx(&text)?
+    };
+    if map.width == 0 || map.tile_width == 0 || map.tile_height == 0 {
+        return Err("The map has no size.".to_string());
+    }
+    Ok(convert(&map, mapping))
+}
+
+fn convert(map: &TiledMap, mapping: &TiledMapping) -> (LevelSave, Vec<ImportIssue>) {
+    let mut level = LevelSave {
+        world_bounds: WorldBounds::new(0, 0, map.width as i32, map.height as i32),
+        ..LevelSave::default()
+    };
+    let mut issues = Vec::new();
+    for layer in &map.tile_layers {
+        for (index, gid) in layer.iter().enumerate() {
+            let gid = gid & !FLIP_FLAGS;
+            if gid == 0 {
+                continue;
+            }
+            // Tiled counts rows downwards from the top, the game counts upwards from the bottom.
+            let pos = Pos::new(
+                (index as u32 % map.width) as i32,
+                map.height as i32 - 1 - (index as u32 / map.width) as i32,
+            );
+            match map_tile(gid, map, mapping) {
+                Ok(key) => {
+                    level.tiles.insert(pos, key.clone());
+                }
+                Err((tileset, id)) => issues.push(ImportIssue::UnmappedTile { tileset, id, pos }),
+            }
+        }
+    }
+    for object in &map.objects {
+        let pos = object_pos(object, map);
+        let key = mapping
+            .objects
+            .get(&object.object_type)
+            .or_else(|| object.gid.and_then(|gid| map_tile(gid, map, mapping).ok()));
+        match key {
+            Some(key) => {
+                level.tiles.insert(pos, key.clone());
+            }
+            None => issues.push(ImportIssue::UnmappedObject {
+                object_type: object.object_type.clone(),
+                pos,
+            }),
+        }
+    }
+    (level, issues)
+}
+
+/// Returns the tile definition key that the mapping gives for the tile with the given global
+/// tile id. If there is none, returns the name of the tileset and the id of the tile within it.
+fn map_tile<'a>(
+    gid: u32,
+    map: &TiledMap,
+    mapping: &'a TiledMapping,
+) -> Result<&'a String, (String, u32)> {
+    let gid = gid & !FLIP_FLAGS;
+    let tileset = map
+        .tilesets
+        .iter()
+        .filter(|tileset| tileset.first_gid <= gid)
+        .max_by_key(|tileset| tileset.first_gid);
+    match tileset {
+        Some(tileset) => {
+            let id = gid - tileset.first_gid;
+            mapping
+                .tiles
+                .get(&tileset.name)
+                .and_then(|ids| ids.get(&id))
+                .ok_or_else(|| (tileset.name.clone(), id))
+        }
+        None => Err((String::new(), gid)),
+    }
+}
+
+/// Returns the position of the bottom-left cell of the object. That is where the game anchors
+/// its tiles.
+fn object_pos(object: &TiledObject, map: &TiledMap) -> Pos {
+    let bottom = if object.gid.is_some() {
+        object.y
+    } else {
+        object.y + object.height
+    };
+    Pos::new(
+        (object.x / map.tile_width as f32).round() as i32,
+        map.height as i32 - (bottom / map.tile_height as f32).round() as i32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> TiledMapping {
+        let mut terrain = HashMap::new();
+        terrain.insert(0, "Block1".to_string());
+        terrain.insert(1, "Ladder".to_string());
+        let mut tiles = HashMap::new();
+        tiles.insert("terrain".to_string(), terrain);
+        let mut objects = HashMap::new();
+        objects.insert("Player".to_string(), "Player".to_string());
+        TiledMapping { tiles, objects }
+    }
+
+    /// A 3 by 2 map that uses an embedded tileset and a tileset stored in its own file.
+    fn map(tile_layer: Vec<u32>) -> TiledMap {
+        TiledMap {
+            width: 3,
+            height: 2,
+            tile_width: 16,
+            tile_height: 16,
+            tilesets: vec![
+                TiledTileset::new(1, Some("terrain"), None),
+                TiledTileset::new(3, None, Some("tilesets/props.tsx")),
+            ],
+            tile_layers: vec![tile_layer],
+            objects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rows_are_counted_from_the_bottom() {
+        let (level, issues) = convert(&map(vec![1, 0, 0, 0, 0, 2]), &mapping());
+        assert!(issues.is_empty());
+        assert_eq!(level.tiles.len(), 2);
+        assert_eq!(level.tiles[&Pos::new(0, 1)], "Block1");
+        assert_eq!(level.tiles[&Pos::new(2, 0)], "Ladder");
+        assert_eq!(level.world_bounds, WorldBounds::new(0, 0, 3, 2));
+    }
+
+    #[test]
+    fn flip_flags_are_ignored() {
+        let (level, issues) = convert(&map(vec![0x8000_0002, 0, 0, 0, 0, 0]), &mapping());
+        assert!(issues.is_empty());
+        assert_eq!(level.tiles[&Pos::new(0, 1)], "Ladder");
+    }
+
+    #[test]
+    fn tiles_of_different_tilesets_are_not_mixed_up() {
+        // Global id 3 is the first tile of the props tileset, which has no mapping. It must not be
+        // imported as the first tile of the terrain tileset.
+        let (level, issues) = convert(&map(vec![1, 3, 0, 0, 0, 0]), &mapping());
+        assert_eq!(level.tiles.len(), 1);
+        assert_eq!(level.tiles[&Pos::new(0, 1)], "Block1");
+        assert_eq!(
+            issues,
+            vec![ImportIssue::UnmappedTile {
+                tileset: "props".to_string(),
+                id: 0,
+                pos: Pos::new(1, 1),
+            }]
+        );
+    }
+
+    #[test]
+    fn objects_are_anchored_at_their_bottom_left_cell() {
+        let mut map = map(vec![0; 6]);
+        map.objects.push(TiledObject {
+            x: 16.,
+            y: 0.,
+            height: 32.,
+            gid: None,
+            object_type: "Player".to_string(),
+        });
+        map.objects.push(TiledObject {
+            x: 32.,
+            y: 32.,
+            height: 16.,
+            gid: Some(1),
+            object_type: String::new(),
+        });
+        map.objects.push(TiledObject {
+            x: 0.,
+            y: 16.,
+            height: 16.,
+            gid: None,
+            object_type: "Dragon".to_string(),
+        });
+        let (level, issues) = convert(&map, &mapping());
+        assert_eq!(level.tiles[&Pos::new(1, 0)], "Player");
+        assert_eq!(level.tiles[&Pos::new(2, 0)], "Block1");
+        assert_eq!(
+            issues,
+            vec![ImportIssue::UnmappedObject {
+                object_type: "Dragon".to_string(),
+                pos: Pos::new(0, 0),
+            }]
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::import::tiled::{TiledMap, TiledObject, TiledTileset};
+
+/// Reads a map in Tiled's TMX format. Tile layers must use the CSV or the XML layer format.
+pub(crate) fn parse_tmx(text: &str) -> Result<TiledMap, String> {
+    let mut map = TiledMap::default();
+    let mut found_map = false;
+    // The tile layer that is being read, if any.
+    let mut layer: Option<Vec<u32>> = None;
+    let mut in_data = false;
+    // Embedded tilesets can hold object groups of their own, with the collision shapes of their
+    // tiles. Only the objects in the map's object layers are imported.
+    let mut in_tileset = false;
+    let mut in_object_layer = false;
+    for event in EventReader::from_str(text) {
+        match event.map_err(|error| format!("Invalid Tiled map: {}", error))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "map" => {
+                    found_map = true;
+                    if attribute(&attributes, "infinite") == Some("1") {
+                        return Err("Infinite maps are not supported.".to_string());
+                    }
+                    map.width = parse_attribute(&attributes, "width")?;
+                    map.height = parse_attribute(&attributes, "height")?;
+                    map.tile_width = parse_attribute(&attributes, "tilewidth")?;
+                    map.tile_height = parse_attribute(&attributes, "tileheight")?;
+                }
+                "tileset" => {
+                    in_tileset = true;
+                    map.tilesets.push(TiledTileset::new(
+                        parse_attribute(&attributes, "firstgid")?,
+                        attribute(&attributes, "name"),
+                        attribute(&attributes, "source"),
+                    ));
+                }
+                "objectgroup" if !in_tileset => in_object_layer = true,
+                "layer" => layer = Some(Vec::new()),
+                "data" if layer.is_some() => {
+                    if attribute(&attributes, "compression").is_some() {
+                        return Err("Compressed tile layers are not supported. \
+                            Set the layer format to CSV."
+                            .to_string());
+                    }
+                    match attribute(&attributes, "encoding") {
+                        None | Some("csv") => in_data = true,
+                        Some(encoding) => {
+                            return Err(format!(
+                                "Tile layers encoded as {} are not supported. \
+                                Set the layer format to CSV.",
+                                encoding
+                            ))
+                        }
+                    }
+                }
+                // In the XML layer format, each tile is a separate element.
+                "tile" if in_data => {
+                    let gid = parse_optional_attribute(&attributes, "gid")?.unwrap_or(0);
+                    if let Some(layer) = layer.as_mut() {
+                        layer.push(gid);
+                    }
+                }
+                "object" if in_object_layer => map.objects.push(TiledObject {
+                    x: parse_optional_attribute(&attributes, "x")?.unwrap_or(0.),
+                    y: parse_optional_attribute(&attributes, "y")?.unwrap_or(0.),
+                    height: parse_optional_attribute(&attributes, "height")?.unwrap_or(0.),
+                    gid: parse_optional_attribute(&attributes, "gid")?,
+                    // Tiled 1.9 renamed the object type to class.
+                    object_type: attribute(&attributes, "type")
+                        .or_else(|| attribute(&attributes, "class"))
+                        .unwrap_or_default()
+                        .to_string(),
+                }),
+                _ => (),
+            },
+            XmlEvent::Characters(csv) if in_data => {
+                if let Some(layer) = layer.as_mut() {
+                    for gid in csv.split(',').map(str::trim).filter(|gid| !gid.is_empty()) {
+                        layer.push(
+                            gid.parse()
+                                .map_err(|_| format!("Invalid tile id {:?}.", gid))?,
+                        );
+                    }
+                }
+            }
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "data" => in_data = false,
+                "tileset" => in_tileset = false,
+                "objectgroup" => in_object_layer = false,
+                "layer" => {
+                    if let Some(layer) = layer.take() {
+                        map.tile_layers.push(layer);
+                    }
+                }
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+    if found_map {
+        Ok(map)
+    } else {
+        Err("The file does not contain a Tiled map.".to_string())
+    }
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name.local_name == name)
+        .map(|attribute| attribute.value.as_str())
+}
+
+fn parse_attribute<T: FromStr>(attributes: &[OwnedAttribute], name: &str) -> Result<T, String> {
+    parse_optional_attribute(attributes, name)?
+        .ok_or_else(|| format!("Missing attribute {:?}.", name))
+}
+
+fn parse_optional_attribute<T: FromStr>(
+    attributes: &[OwnedAttribute],
+    name: &str,
+) -> Result<Option<T>, String> {
+    attribute(attributes, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value {:?} for attribute {:?}.", value, name))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.9" orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" name="terrain" tilewidth="16" tileheight="16" tilecount="2" columns="2">
+  <image source="terrain.png" width="32" height="16"/>
+  <tile id="0">
+   <objectgroup draworder="index">
+    <object id="1" x="0" y="0" width="16" height="16"/>
+   </objectgroup>
+  </tile>
+ </tileset>
+ <tileset firstgid="3" source="props.tsx"/>
+ <layer id="1" name="Terrain" width="2" height="2">
+  <data encoding="csv">
+1,0,
+2,3
+</data>
+ </layer>
+ <objectgroup id="2" name="Objects">
+  <object id="2" type="Player" x="16" y="0" width="16" height="32"/>
+ </objectgroup>
+</map>"#;
+
+    #[test]
+    fn reads_tilesets_and_tile_layers() {
+        let map = parse_tmx(MAP).unwrap();
+        assert_eq!((map.width, map.height), (2, 2));
+        assert_eq!((map.tile_width, map.tile_height), (16, 16));
+        let tilesets = map
+            .tilesets
+            .iter()
+            .map(|tileset| (tileset.first_gid, tileset.name.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(tilesets, vec![(1, "terrain"), (3, "props")]);
+        assert_eq!(map.tile_layers, vec![vec![1, 0, 2, 3]]);
+    }
+
+    #[test]
+    fn only_objects_in_object_layers_are_read() {
+        let map = parse_tmx(MAP).unwrap();
+        assert_eq!(map.objects.len(), 1);
+        assert_eq!(map.objects[0].object_type, "Player");
+    }
+
+    #[test]
+    fn infinite_maps_are_refused() {
+        let map = MAP.replace(r#"infinite="0""#, r#"infinite="1""#);
+        assert!(parse_tmx(&map).is_err());
+    }
+}
@@ -1,21 +1,25 @@
 #![forbid(unsafe_code)]
 
-//! This crate is not really used right now. The plan is to be able to run automated tests
+//! Automated checks that simulate game play. The plan is to be able to run automated tests
 //! with AIs playing the role of the player to prove that the player will or will not be able to
 //! make certain jumps, etc.
 //!
 //! Being able to guarantee that a player cannot make a certain jump is of course very important in
 //! a puzzle game, because these sorts of bugs could make puzzles trivially easy.
 //!
-//! This crate can also eventually be leveraged to create automated tests that prove that a certain
-//! puzzle is solvable.
-
-// TODO: Change this crate to a bin.
+//! The `solver` module can prove that a puzzle is solvable. If it finds no solution, that is a
+//! strong hint that the puzzle cannot be solved, but not proof: it does not try every possible
+//! movement, such as jumps with a running start.
+//! The `checks` module runs structural checks and the solver on level files; the dsf_checks binary
+//! uses it to validate all levels from the command line.
+//! The `scenarios` module runs scripted movement scenarios, such as those in
+//! assets/tests/jump_scenarios.ron, to prove which jumps the player can and cannot make.
+//! The `import` module converts maps made in Tiled into levels.
 
 #[macro_use]
 extern crate log;
 
-pub mod components;
-pub mod resources;
-pub mod states;
-pub mod systems;
+pub mod checks;
+pub mod import;
+pub mod scenarios;
+pub mod solver;
@@ -0,0 +1,332 @@
+#![forbid(unsafe_code)]
+
+//! Command-line tool that validates levels. For each level, it checks the level's structure and
+//! then runs the solver to prove the level can be won. It also runs the movement scenarios, which
+//! prove that the player can or cannot make certain jumps.
+//!
+//! Usage:
+//! - `dsf_checks`: check all levels in the game's levels directory and run the jump scenarios.
+//! - `dsf_checks PATH`: check only the given level file, or all level files in the given directory.
+//! - `dsf_checks --scenarios [FILE]`: run only the movement scenarios in the given file.
+//! - `dsf_checks --migrate [PATH]`: upgrade the given level file, or all level files in the given
+//!   directory, to the current version of the level format. Defaults to the levels directory.
+//! - `dsf_checks --convert FROM TO`: convert a level between the RON and the text format. The
+//!   format of each file is chosen by its extension.
+//! - `dsf_checks --import-tiled MAP TO [MAPPING]`: convert a map made in Tiled (.tmx or .json) into
+//!   a level. MAPPING defaults to assets/world/tiled_mapping.ron.
+//! - `dsf_checks --replay REPLAY [LEVEL]`: play a recorded replay in the headless simulation and
+//!   check that it wins the level. LEVEL defaults to the level the replay was recorded in.
+//!
+//! Exits with a non-zero status if any check fails, so it can be used in pre-merge hooks.
+
+#[macro_use]
+extern crate log;
+
+use std::path::PathBuf;
+use std::process;
+
+use amethyst::config::Config;
+use amethyst::LoggerConfig;
+use log::LevelFilter;
+
+use dsf_checks::checks::{check_level, find_level_files};
+use dsf_checks::import::{import_tiled_map, TiledMapping};
+use dsf_checks::scenarios::run_scenario_file;
+use dsf_checks::solver::Solver;
+use dsf_core::levels::{
+    load_level_save, load_tile_definitions, migrate_level_file, save_level_file, validate,
+    CURRENT_LEVEL_VERSION,
+};
+use dsf_core::resources::{hash_level_file, MovementConfig, Replay, TileDefinitions};
+use dsf_core::sim::Simulation;
+use dsf_core::utility::files::{get_assets_dir, get_config_dir, get_levels_dir, get_world_dir};
+
+fn main() {
+    amethyst::Logger::from_config(LoggerConfig {
+        level_filter: LevelFilter::Warn,
+        ..LoggerConfig::default()
+    })
+    .start();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let tile_defs = load_tile_definitions().unwrap_or_else(|error| {
+        error!("Failed to load tile definitions: {:?}", error);
+        process::exit(2);
+    });
+    match args.first().map(|arg| arg.as_str()) {
+        Some("--migrate") => {
+            let path = args
+                .get(1)
+                .map(PathBuf::from)
+                .unwrap_or_else(get_levels_dir);
+            if !migrate_levels(&path, &tile_defs) {
+                process::exit(1);
+            }
+            return;
+        }
+        Some("--convert") => {
+            if let (Some(from), Some(to)) = (args.get(1), args.get(2)) {
+                convert_level(&PathBuf::from(from), &PathBuf::from(to), &tile_defs);
+            } else {
+                error!("Usage: dsf_checks --convert FROM TO");
+                process::exit(2);
+            }
+            return;
+        }
+        Some("--import-tiled") => {
+            if let (Some(map), Some(to)) = (args.get(1), args.get(2)) {
+                let mapping_file = args
+                    .get(3)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(default_tiled_mapping_file);
+                import_tiled(
+                    &PathBuf::from(map),
+                    &PathBuf::from(to),
+                    &mapping_file,
+                    &tile_defs,
+                );
+            } else {
+                error!("Usage: dsf_checks --import-tiled MAP TO [MAPPING]");
+                process::exit(2);
+            }
+            return;
+        }
+        Some("--replay") => {
+            if let Some(replay_file) = args.get(1) {
+                let level_file = args.get(2).map(PathBuf::from);
+                if !check_replay(&PathBuf::from(replay_file), level_file, &tile_defs) {
+                    process::exit(1);
+                }
+            } else {
+                error!("Usage: dsf_checks --replay REPLAY [LEVEL]");
+                process::exit(2);
+            }
+            return;
+        }
+        _ => (),
+    }
+    let (level_path, scenario_file) = match args.first().map(|arg| arg.as_str()) {
+        Some("--scenarios") => (
+            None,
+            Some(
+                args.get(1)
+                    .map(PathBuf::from)
+                    .unwrap_or_else(default_scenario_file),
+            ),
+        ),
+        Some(path) => (Some(PathBuf::from(path)), None),
+        None => (Some(get_levels_dir()), Some(default_scenario_file())),
+    };
+    let config = load_movement_config();
+    let mut all_passed = true;
+    if let Some(level_path) = level_path {
+        all_passed &= check_levels(&level_path, &tile_defs, &config);
+    }
+    if let Some(scenario_file) = scenario_file {
+        all_passed &= run_scenarios(&scenario_file, &tile_defs, &config);
+    }
+    if !all_passed {
+        process::exit(1);
+    }
+}
+
+/// Unlike the game, don't fall back to the default movement config: its speed is zero, so the
+/// solver would find that no level can be solved.
+fn load_movement_config() -> MovementConfig {
+    MovementConfig::load(&get_config_dir().join("movement.ron")).unwrap_or_else(|error| {
+        error!("Failed to load movement config: {:?}", error);
+        process::exit(2);
+    })
+}
+
+fn default_scenario_file() -> PathBuf {
+    get_assets_dir().join("tests/jump_scenarios.ron")
+}
+
+fn default_tiled_mapping_file() -> PathBuf {
+    get_world_dir().join("tiled_mapping.ron")
+}
+
+/// Checks all levels at the given path. Returns true iff all levels passed.
+fn check_levels(path: &PathBuf, tile_defs: &TileDefinitions, config: &MovementConfig) -> bool {
+    let level_files = find_level_files(path);
+    if level_files.is_empty() {
+        error!("No level files found at {:?}", path);
+        process::exit(2);
+    }
+    let solver = Solver::default();
+    let nr_failed = level_files
+        .iter()
+        .map(|level_file| check_level(level_file, tile_defs, config, &solver))
+        .filter(|report| {
+            print!("{}", report);
+            !report.passed()
+        })
+        .count();
+    println!(
+        "{} of {} levels passed.",
+        level_files.len() - nr_failed,
+        level_files.len()
+    );
+    nr_failed == 0
+}
+
+/// Rewrites all level files at the given path in the current version of the level format.
+/// Returns true iff all levels could be migrated.
+fn migrate_levels(path: &PathBuf, tile_defs: &TileDefinitions) -> bool {
+    let level_files = find_level_files(path);
+    if level_files.is_empty() {
+        error!("No level files found at {:?}", path);
+        process::exit(2);
+    }
+    let nr_failed = level_files
+        .iter()
+        .filter(
+            |level_file| match migrate_level_file(level_file, tile_defs) {
+                Ok(version) if version < CURRENT_LEVEL_VERSION => {
+                    println!(
+                        "{:?}: migrated from version {} to {}.",
+                        level_file, version, CURRENT_LEVEL_VERSION
+                    );
+                    false
+                }
+                Ok(_) => {
+                    println!("{:?}: already up to date.", level_file);
+                    false
+                }
+                Err(error) => {
+                    println!("{:?}: failed to migrate: {:?}", level_file, error);
+                    true
+                }
+            },
+        )
+        .count();
+    println!(
+        "{} of {} levels are up to date.",
+        level_files.len() - nr_failed,
+        level_files.len()
+    );
+    nr_failed == 0
+}
+
+/// Reads the level in the first file and writes it to the second file.
+fn convert_level(from: &PathBuf, to: &PathBuf, tile_defs: &TileDefinitions) {
+    let result = load_level_save(from).and_then(|level| save_level_file(to, &level, tile_defs));
+    if let Err(error) = result {
+        error!("Failed to convert {:?} to {:?}: {:?}", from, to, error);
+        process::exit(2);
+    }
+    println!("Converted {:?} to {:?}.", from, to);
+}
+
+/// Converts the Tiled map into a level and writes it to the given file. Reports any tiles that could
+/// not be imported, as well as any issues with the resulting level. The level is written
+/// regardless, so the issues can be fixed in the editor.
+fn import_tiled(map: &PathBuf, to: &PathBuf, mapping_file: &PathBuf, tile_defs: &TileDefinitions) {
+    let mapping = TiledMapping::load(mapping_file).unwrap_or_else(|error| {
+        error!(
+            "Failed to load Tiled mapping {:?}: {:?}",
+            mapping_file, error
+        );
+        process::exit(2);
+    });
+    let (level, issues) = import_tiled_map(map, &mapping).unwrap_or_else(|error| {
+        error!("Failed to import {:?}: {}", map, error);
+        process::exit(2);
+    });
+    let issues = issues
+        .iter()
+        .map(|issue| issue.to_string())
+        .chain(
+            validate(&level, tile_defs)
+                .iter()
+                .map(|issue| issue.to_string()),
+        )
+        .collect::<Vec<_>>();
+    if let Err(error) = save_level_file(to, &level, tile_defs) {
+        error!("Failed to write {:?}: {:?}", to, error);
+        process::exit(2);
+    }
+    println!("Imported {:?} into {:?}.", map, to);
+    for issue in &issues {
+        println!("    - {}", issue);
+    }
+}
+
+/// Plays the replay in the headless simulation. Returns true iff the replay wins the level.
+fn check_replay(
+    replay_file: &PathBuf,
+    level_file: Option<PathBuf>,
+    tile_defs: &TileDefinitions,
+) -> bool {
+    let replay = Replay::load(replay_file).unwrap_or_else(|error| {
+        error!("Failed to load replay {:?}: {:?}", replay_file, error);
+        process::exit(2);
+    });
+    let level_file = level_file.unwrap_or_else(|| replay.level_file.clone());
+    match hash_level_file(&level_file) {
+        Ok(hash) if hash != replay.level_hash => warn!(
+            "The level {:?} has changed since the replay was recorded.",
+            level_file
+        ),
+        Ok(_) => (),
+        Err(error) => {
+            error!("Failed to read level {:?}: {:?}", level_file, error);
+            process::exit(2);
+        }
+    }
+    let level = load_level_save(&level_file).unwrap_or_else(|error| {
+        error!("Failed to load level {:?}: {:?}", level_file, error);
+        process::exit(2);
+    });
+    let mut sim =
+        Simulation::new(level, tile_defs.clone(), load_movement_config()).unwrap_or_else(|| {
+            error!("Level {:?} does not contain a player.", level_file);
+            process::exit(2);
+        });
+    for input in replay.iter_inputs() {
+        sim.step(&input);
+        if sim.has_won() {
+            break;
+        }
+    }
+    if sim.has_won() {
+        println!(
+            "{:?}: won the level after {} of {} ticks.",
+            replay_file,
+            sim.ticks(),
+            replay.nr_ticks()
+        );
+    } else {
+        println!(
+            "{:?}: did not win the level in {} ticks.",
+            replay_file,
+            replay.nr_ticks()
+        );
+    }
+    sim.has_won()
+}
+
+/// Runs all movement scenarios in the given file. Returns true iff all scenarios passed.
+fn run_scenarios(
+    scenario_file: &PathBuf,
+    tile_defs: &TileDefinitions,
+    config: &MovementConfig,
+) -> bool {
+    let reports = run_scenario_file(scenario_file, tile_defs, config).unwrap_or_else(|error| {
+        error!("Failed to run scenarios in {:?}: {}", scenario_file, error);
+        process::exit(2);
+    });
+    let nr_failed = reports
+        .iter()
+        .filter(|report| {
+            print!("{}", report);
+            !report.passed
+        })
+        .count();
+    println!(
+        "{} of {} scenarios passed.",
+        reports.len() - nr_failed,
+        reports.len()
+    );
+    nr_failed == 0
+}
@@ -1,2 +0,0 @@
-mod movement_test;
-pub use self::movement_test::*;
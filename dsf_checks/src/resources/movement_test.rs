@@ -1,2 +0,0 @@
-#[derive(Debug, Default)]
-pub struct TestStage {}
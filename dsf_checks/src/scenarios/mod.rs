@@ -0,0 +1,3 @@
+mod scenario;
+
+pub use self::scenario::*;
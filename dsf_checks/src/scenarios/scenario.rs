@@ -0,0 +1,171 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use amethyst::config::Config;
+use serde::{Deserialize, Serialize};
+
+use dsf_core::components::Pos;
+use dsf_core::levels::load_level_save;
+use dsf_core::resources::{Archetype, MovementConfig, TileDefinitions};
+use dsf_core::sim::{PlayerInput, Simulation};
+
+/// A file full of movement scenarios, such as assets/tests/jump_scenarios.ron.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioList {
+    pub scenarios: Vec<Scenario>,
+}
+
+/// A scripted piece of game play with an expected outcome.
+/// Used to prove that the player can or cannot make certain jumps.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    /// The level file, relative to the directory that contains the scenario file.
+    pub level: String,
+    /// If given, the player starts at this position. Any players in the level itself are removed.
+    #[serde(default)]
+    pub start: Option<Pos>,
+    /// The inputs to feed to the simulation, in order.
+    pub inputs: Vec<InputStep>,
+    pub expect: Expectation,
+}
+
+/// Hold the given input for the given number of ticks.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputStep {
+    pub input: PlayerInput,
+    pub ticks: usize,
+}
+
+/// The outcome that a scenario expects.
+/// The player stands at a position if they are on the ground or on a ladder at that position,
+/// for at least one tick. Passing through it mid-air does not count.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum Expectation {
+    /// At some point during the scenario, the player stands at this position.
+    Reaches(Pos),
+    /// At no point during the scenario does the player stand at this position.
+    NeverReaches(Pos),
+}
+
+/// The outcome of running a single scenario.
+#[derive(Debug)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub passed: bool,
+    /// Explains why the scenario failed. Empty if it passed.
+    pub details: String,
+}
+
+impl fmt::Display for ScenarioReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} {}",
+            if self.passed { "PASS" } else { "FAIL" },
+            self.name
+        )?;
+        if !self.details.is_empty() {
+            writeln!(f, "    - {}", self.details)?;
+        }
+        Ok(())
+    }
+}
+
+/// Loads all scenarios from the given file and runs them.
+/// Levels are looked up relative to the directory that contains the scenario file.
+pub fn run_scenario_file(
+    scenario_file: &PathBuf,
+    tile_defs: &TileDefinitions,
+    config: &MovementConfig,
+) -> Result<Vec<ScenarioReport>, String> {
+    let list = ScenarioList::load(scenario_file)
+        .map_err(|error| format!("failed to load scenarios: {:?}", error))?;
+    let dir = scenario_file
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_default();
+    Ok(list
+        .scenarios
+        .iter()
+        .map(|scenario| run_scenario(scenario, &dir, tile_defs, config))
+        .collect())
+}
+
+/// Runs the scenario in the headless simulation and checks whether the expectation holds.
+pub fn run_scenario(
+    scenario: &Scenario,
+    dir: &Path,
+    tile_defs: &TileDefinitions,
+    config: &MovementConfig,
+) -> ScenarioReport {
+    let failure = |details: String| ScenarioReport {
+        name: scenario.name.clone(),
+        passed: false,
+        details,
+    };
+    let mut level = match load_level_save(dir.join(&scenario.level)) {
+        Ok(level) => level,
+        Err(error) => return failure(format!("failed to load level: {:?}", error)),
+    };
+    if let Some(start) = scenario.start {
+        let player_key = tile_defs
+            .map
+            .iter()
+            .find(|(_, tile_def)| tile_def.archetype == Some(Archetype::Player))
+            .map(|(key, _)| key.clone());
+        if let Some(player_key) = player_key {
+            level
+                .tiles
+                .retain(|_, key| tile_defs.get(key).archetype != Some(Archetype::Player));
+            level.tiles.insert(start, player_key);
+        } else {
+            return failure("there is no tile definition for the player".to_string());
+        }
+    }
+    let mut sim = match Simulation::new(level, tile_defs.clone(), config.clone()) {
+        Some(sim) => sim,
+        None => return failure("level does not contain a player".to_string()),
+    };
+    let target = match scenario.expect {
+        Expectation::Reaches(pos) | Expectation::NeverReaches(pos) => pos,
+    };
+    let stands_on_target = |sim: &Simulation| {
+        (sim.steering().is_grounded() || sim.steering().is_climbing())
+            && sim.steering().pos == target
+    };
+    let mut reached_at = if stands_on_target(&sim) {
+        Some(0)
+    } else {
+        None
+    };
+    for step in &scenario.inputs {
+        for _ in 0..step.ticks {
+            sim.step(&step.input);
+            if reached_at.is_none() && stands_on_target(&sim) {
+                reached_at = Some(sim.ticks());
+            }
+        }
+    }
+    let final_pos = sim.steering().pos;
+    match (scenario.expect, reached_at) {
+        (Expectation::Reaches(_), None) => failure(format!(
+            "never stood at {:?}, ended at {:?} in mode {:?}",
+            target,
+            final_pos,
+            sim.steering().mode
+        )),
+        (Expectation::NeverReaches(_), Some(tick)) => {
+            failure(format!("stood at {:?} at tick {}", target, tick))
+        }
+        _ => ScenarioReport {
+            name: scenario.name.clone(),
+            passed: true,
+            details: String::new(),
+        },
+    }
+}
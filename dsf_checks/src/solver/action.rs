@@ -0,0 +1,176 @@
+use dsf_core::components::Direction1D;
+use dsf_core::sim::{PlayerInput, Simulation};
+use serde::{Deserialize, Serialize};
+
+/// How many ticks a movement key may be held before we conclude that it does not move the player.
+/// This must comfortably exceed the turn-around allowance from the MovementConfig.
+const MAX_HOLD_TICKS: usize = 30;
+/// How many ticks an action may take before the player comes to rest again. If the player is still
+/// moving after this many ticks, they are falling endlessly through the wrapping level.
+const MAX_SETTLE_TICKS: usize = 600;
+
+/// A discrete action the player can take while standing still on the ground or on a ladder.
+/// Every action starts and ends with the player at rest, which lets the solver treat the level as
+/// a graph of grid states.
+///
+/// Note that jumps are only ever started from a standstill. The player may be able to make a jump
+/// with a running start that this set of actions cannot express.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Action {
+    /// Walk a single tile to the left, or step off a ladder to the left.
+    WalkLeft,
+    /// Walk a single tile to the right, or step off a ladder to the right.
+    WalkRight,
+    /// Climb up a single tile.
+    ClimbUp,
+    /// Climb down a single tile.
+    ClimbDown,
+    /// Jump straight up, or let go of a ladder.
+    JumpUp,
+    /// Jump towards the left.
+    JumpLeft,
+    /// Jump towards the right.
+    JumpRight,
+    /// Face left and use the equipped tool.
+    UseToolLeft,
+    /// Face right and use the equipped tool.
+    UseToolRight,
+}
+
+/// The result of performing a single action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionOutcome {
+    /// The player is at rest again and can take another action.
+    AtRest,
+    /// The player won the level while performing the action.
+    Won,
+    /// The player never came to rest. They are stuck falling forever.
+    NeverSettled,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::WalkLeft,
+        Action::WalkRight,
+        Action::ClimbUp,
+        Action::ClimbDown,
+        Action::JumpUp,
+        Action::JumpLeft,
+        Action::JumpRight,
+        Action::UseToolLeft,
+        Action::UseToolRight,
+    ];
+
+    /// Whether it makes sense to try this action in the current state of the simulation.
+    /// While a tool is equipped, pressing jump uses the tool instead of jumping.
+    pub fn is_applicable(self, sim: &Simulation) -> bool {
+        let equipped = sim.player().equipped.is_some();
+        match self {
+            Action::JumpUp | Action::JumpLeft | Action::JumpRight => !equipped,
+            Action::UseToolLeft | Action::UseToolRight => equipped && sim.steering().is_grounded(),
+            _ => true,
+        }
+    }
+
+    /// Performs the action by feeding the corresponding inputs to the simulation, then lets go of
+    /// all controls until the player comes to rest.
+    pub fn perform(self, sim: &mut Simulation) -> ActionOutcome {
+        let start = sim.steering().clone();
+        let moved = |sim: &Simulation| {
+            sim.steering().destination != start.destination || sim.steering().mode != start.mode
+        };
+        match self {
+            Action::WalkLeft => hold(sim, &horizontal(-1.), moved),
+            Action::WalkRight => hold(sim, &horizontal(1.), moved),
+            Action::ClimbUp => hold(sim, &vertical(1.), moved),
+            Action::ClimbDown => hold(sim, &vertical(-1.), moved),
+            Action::JumpUp => sim.step(&jump(0.)),
+            Action::JumpLeft => sim.step(&jump(-1.)),
+            Action::JumpRight => sim.step(&jump(1.)),
+            Action::UseToolLeft => use_tool(sim, -1.),
+            Action::UseToolRight => use_tool(sim, 1.),
+        }
+        settle(sim)
+    }
+}
+
+/// Performs all given actions in order. Stops early if the level is won or the player never comes
+/// to rest.
+pub fn perform_all(actions: &[Action], sim: &mut Simulation) -> ActionOutcome {
+    for action in actions {
+        let outcome = action.perform(sim);
+        if outcome != ActionOutcome::AtRest {
+            return outcome;
+        }
+    }
+    ActionOutcome::AtRest
+}
+
+/// Lets go of all controls until the player comes to rest or wins the level.
+pub fn settle(sim: &mut Simulation) -> ActionOutcome {
+    let neutral = PlayerInput::default();
+    for _ in 0..MAX_SETTLE_TICKS {
+        if sim.has_won() {
+            return ActionOutcome::Won;
+        }
+        if sim.is_at_rest() {
+            return ActionOutcome::AtRest;
+        }
+        sim.step(&neutral);
+    }
+    if sim.has_won() {
+        ActionOutcome::Won
+    } else if sim.is_at_rest() {
+        ActionOutcome::AtRest
+    } else {
+        ActionOutcome::NeverSettled
+    }
+}
+
+/// Holds the given input until the given condition is met, or until it is clear that the input
+/// does not do anything.
+fn hold<F>(sim: &mut Simulation, input: &PlayerInput, until: F)
+where
+    F: Fn(&Simulation) -> bool,
+{
+    for _ in 0..MAX_HOLD_TICKS {
+        sim.step(input);
+        if sim.has_won() || until(sim) {
+            return;
+        }
+    }
+}
+
+/// Turns the player around if needed, then presses jump to use the equipped tool.
+fn use_tool(sim: &mut Simulation, direction: f32) {
+    if sim.steering().facing.x != Direction1D::new(direction) {
+        // Tapping the opposite direction while standing still only turns the player around.
+        sim.step(&horizontal(direction));
+        if settle(sim) != ActionOutcome::AtRest {
+            return;
+        }
+    }
+    sim.step(&jump(0.));
+}
+
+fn horizontal(move_x: f32) -> PlayerInput {
+    PlayerInput {
+        move_x,
+        ..PlayerInput::default()
+    }
+}
+
+fn vertical(move_y: f32) -> PlayerInput {
+    PlayerInput {
+        move_y,
+        ..PlayerInput::default()
+    }
+}
+
+fn jump(move_x: f32) -> PlayerInput {
+    PlayerInput {
+        move_x,
+        move_y: 0.,
+        jump: true,
+    }
+}
@@ -0,0 +1,5 @@
+mod action;
+mod search;
+
+pub use self::action::*;
+pub use self::search::*;
@@ -0,0 +1,234 @@
+use std::collections::{HashSet, VecDeque};
+
+use dsf_core::components::Pos;
+use dsf_core::resources::ToolType;
+use dsf_core::sim::{PlayerInput, Simulation};
+
+use crate::solver::{settle, Action, ActionOutcome};
+
+/// Everything that distinguishes one resting state of the level from another.
+/// Two simulations with the same key will have exactly the same future, so the solver only needs to
+/// explore each key once.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StateKey {
+    pub pos: Pos,
+    pub climbing: bool,
+    /// The direction the player is facing along the x-axis. Matters for tools.
+    pub facing: i32,
+    pub equipped: Option<ToolType>,
+    /// Keys left in the level, sorted.
+    pub keys_left: Vec<Pos>,
+    /// Tools left in the level, sorted.
+    pub tools_left: Vec<Pos>,
+    /// Blocks that were broken, sorted.
+    pub broken_blocks: Vec<Pos>,
+}
+
+impl From<&Simulation> for StateKey {
+    fn from(sim: &Simulation) -> Self {
+        let mut keys_left = sim.win_condition().keys.iter().copied().collect::<Vec<_>>();
+        keys_left.sort();
+        let mut tools_left = sim.tools_left();
+        tools_left.sort();
+        let mut broken_blocks = sim.broken_blocks().to_vec();
+        broken_blocks.sort();
+        StateKey {
+            pos: sim.steering().pos,
+            climbing: sim.steering().is_climbing(),
+            facing: sim.steering().facing.x.signum_i(),
+            equipped: sim.player().equipped,
+            keys_left,
+            tools_left,
+            broken_blocks,
+        }
+    }
+}
+
+/// The verdict of the solver.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    /// The level can be won by performing these actions in order, starting from the level's
+    /// initial state.
+    Solved(Vec<Action>),
+    /// Every state that the solver's actions can reach from the start of the level was explored,
+    /// and none of them leads to a win. This is not proof that the level cannot be won: the
+    /// actions do not cover everything a player can do, such as jumping with a running start.
+    NoSolutionFound { explored_states: usize },
+    /// The player never comes to rest after the level starts, so the solver cannot take a
+    /// single action. They are stuck falling forever.
+    NeverSettled,
+    /// The search gave up after exploring the maximum number of states. The level may or may not
+    /// be solvable.
+    Inconclusive { explored_states: usize },
+}
+
+impl Solution {
+    pub fn is_solved(&self) -> bool {
+        matches!(self, Solution::Solved(_))
+    }
+}
+
+/// Tries to prove that a level can be won, by exploring every resting state the player can reach
+/// with the discrete actions in `Action`. If none of those states wins the level, a player might
+/// still win it with movement that the actions cannot express.
+///
+/// The search is breadth-first, so the winning action sequence it finds is as short as possible.
+/// All movement runs through the real game rules in the headless simulation.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    /// The search is aborted after exploring this many distinct states.
+    pub max_states: usize,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver { max_states: 50_000 }
+    }
+}
+
+impl Solver {
+    pub fn new(max_states: usize) -> Self {
+        Solver { max_states }
+    }
+
+    pub fn solve(&self, mut sim: Simulation) -> Solution {
+        // Every level starts with the player in the grounded mode, even if there is no ground
+        // beneath their feet. Run a single tick, so they start falling if that is the case.
+        sim.step(&PlayerInput::default());
+        match settle(&mut sim) {
+            ActionOutcome::Won => return Solution::Solved(vec![]),
+            ActionOutcome::NeverSettled => return Solution::NeverSettled,
+            ActionOutcome::AtRest => (),
+        }
+        // Each explored state remembers the state it was reached from and the action that got
+        // it there, so the winning path can be reconstructed at the end.
+        let mut explored: Vec<(Option<usize>, Option<Action>)> = vec![(None, None)];
+        let mut visited = HashSet::new();
+        visited.insert(StateKey::from(&sim));
+        let mut frontier = VecDeque::new();
+        frontier.push_back((0, sim));
+        while let Some((index, sim)) = frontier.pop_front() {
+            for action in Action::ALL.iter() {
+                if !action.is_applicable(&sim) {
+                    continue;
+                }
+                let mut next = sim.clone();
+                match action.perform(&mut next) {
+                    ActionOutcome::Won => {
+                        let mut actions = reconstruct_path(&explored, index);
+                        actions.push(*action);
+                        return Solution::Solved(actions);
+                    }
+                    ActionOutcome::NeverSettled => continue,
+                    ActionOutcome::AtRest => (),
+                }
+                if visited.insert(StateKey::from(&next)) {
+                    if explored.len() >= self.max_states {
+                        return Solution::Inconclusive {
+                            explored_states: explored.len(),
+                        };
+                    }
+                    explored.push((Some(index), Some(*action)));
+                    frontier.push_back((explored.len() - 1, next));
+                }
+            }
+        }
+        Solution::NoSolutionFound {
+            explored_states: explored.len(),
+        }
+    }
+}
+
+fn reconstruct_path(explored: &[(Option<usize>, Option<Action>)], mut index: usize) -> Vec<Action> {
+    let mut actions = Vec::new();
+    while let (Some(parent), Some(action)) = explored[index] {
+        actions.push(action);
+        index = parent;
+    }
+    actions.reverse();
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::perform_all;
+    use amethyst::config::Config;
+    use dsf_core::levels::{load_level_save, load_tile_definitions, LevelSave};
+    use dsf_core::resources::{MovementConfig, WorldBounds};
+    use dsf_core::utility::files::{get_assets_dir, get_config_dir};
+
+    /// Loads one of the jump test levels and puts the player on the near platform and the door on
+    /// the far platform, which starts at the given x. The level is walled in on all sides, so the
+    /// player cannot get around the gap by wrapping around the level. The gap becomes a pit that
+    /// is too deep to jump out of.
+    fn jump_level(file_name: &str, far_platform_x: i32) -> LevelSave {
+        let mut level = load_level_save(get_assets_dir().join("tests").join(file_name))
+            .expect("Failed to load test level.");
+        let right_wall_x = far_platform_x + 4;
+        level.world_bounds = WorldBounds::new(-1, -6, right_wall_x + 2, 12);
+        for y in -6..6 {
+            level.tiles.insert(Pos::new(-1, y), "Block1".to_string());
+            level
+                .tiles
+                .insert(Pos::new(right_wall_x, y), "Block1".to_string());
+        }
+        for x in 0..right_wall_x {
+            level.tiles.insert(Pos::new(x, -6), "Block1".to_string());
+        }
+        level.tiles.insert(Pos::new(0, 0), "Player".to_string());
+        level
+            .tiles
+            .insert(Pos::new(far_platform_x, 0), "Door".to_string());
+        level
+    }
+
+    fn simulation(level: LevelSave) -> Simulation {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let config = MovementConfig::load(get_config_dir().join("movement.ron"))
+            .expect("Failed to load movement config.");
+        Simulation::new(level, tile_defs, config).expect("Level has no player.")
+    }
+
+    #[test]
+    fn jumps_across_a_2_wide_gap() {
+        let sim = simulation(jump_level("jump_2_wide.ron", 6));
+        let actions = match Solver::default().solve(sim.clone()) {
+            Solution::Solved(actions) => actions,
+            solution => panic!("Expected a solution, got {:?}", solution),
+        };
+        assert!(actions.contains(&Action::JumpRight));
+        let mut replayed = sim;
+        replayed.step(&PlayerInput::default());
+        assert_eq!(perform_all(&actions, &mut replayed), ActionOutcome::Won);
+    }
+
+    #[test]
+    fn finds_no_way_across_a_5_wide_gap() {
+        let sim = simulation(jump_level("jump_5_wide.ron", 9));
+        match Solver::default().solve(sim) {
+            Solution::NoSolutionFound { explored_states } => assert!(explored_states > 1),
+            solution => panic!("Expected no solution, got {:?}", solution),
+        }
+    }
+
+    #[test]
+    fn gives_up_after_the_maximum_number_of_states() {
+        let sim = simulation(jump_level("jump_5_wide.ron", 9));
+        assert_eq!(
+            Solver::new(2).solve(sim),
+            Solution::Inconclusive { explored_states: 2 }
+        );
+    }
+
+    #[test]
+    fn reports_a_player_that_never_comes_to_rest() {
+        let mut level = LevelSave::default();
+        level.tiles.insert(Pos::new(0, 0), "Player".to_string());
+        level.tiles.insert(Pos::new(8, 0), "Door".to_string());
+        assert_eq!(
+            Solver::default().solve(simulation(level)),
+            Solution::NeverSettled
+        );
+    }
+}
@@ -1,5 +0,0 @@
-mod movement_test;
-mod setup;
-
-pub use self::movement_test::*;
-pub use self::setup::*;
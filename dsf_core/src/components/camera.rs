@@ -29,6 +29,51 @@ pub struct CameraFrame {
     /// of the panning controls. This will be faster than the speed at which the player can pan the
     /// camera around, resulting in a sort of rubber banding effect.
     pub panning_recovery_speed: f32,
+    /// The current zoom factor. At 1.0, the camera shows the default amount of the world.
+    /// Higher values zoom in, lower values zoom out.
+    pub zoom: f32,
+    /// The zoom factor that the camera is moving towards. The camera zooms smoothly rather than
+    /// jumping straight to a new zoom factor.
+    pub target_zoom: f32,
+    /// How quickly the zoom factor approaches the target zoom factor. Higher is faster.
+    pub zoom_speed: f32,
+    /// How much the target zoom factor changes per step of the mouse wheel, as a fraction of the
+    /// current target zoom factor.
+    pub zoom_step: f32,
+    /// The lowest zoom factor the user can zoom out to with the mouse wheel.
+    pub min_zoom: f32,
+    /// The highest zoom factor the user can zoom in to with the mouse wheel.
+    pub max_zoom: f32,
+    /// If true, the camera centers on the level and zooms to show the whole level at once.
+    /// This ignores the zoom limits, so even very large levels fit on the screen.
+    pub fit_level: bool,
+    /// What the camera does near the edges of the level.
+    pub edge_mode: EdgeMode,
+    /// How quickly the camera catches up with the player. Higher is faster.
+    pub follow_speed: f32,
+    /// The point the camera frame is currently centered on. The camera moves this point smoothly
+    /// towards the player. None until the camera has found the player for the first time.
+    pub focus: Option<Vector2<f32>>,
+}
+
+/// Determines what the camera does near the edges of the level.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum EdgeMode {
+    /// Levels wrap around, so the camera looks past the edges of the level and shows the tiles on
+    /// the other side. When the player wraps around, the camera wraps around with them.
+    Wrap,
+    /// The camera never shows anything outside of the level. If the level is smaller than the
+    /// screen, the level is centered on the screen instead.
+    Clamp,
+}
+
+impl EdgeMode {
+    pub fn next(self) -> Self {
+        match self {
+            EdgeMode::Wrap => EdgeMode::Clamp,
+            EdgeMode::Clamp => EdgeMode::Wrap,
+        }
+    }
 }
 
 impl Component for CameraFrame {
@@ -42,6 +87,16 @@ impl Default for CameraFrame {
             max_pan: 5.,
             panning_speed: 10.,
             panning_recovery_speed: 40.,
+            zoom: 1.,
+            target_zoom: 1.,
+            zoom_speed: 10.,
+            zoom_step: 0.1,
+            min_zoom: 0.25,
+            max_zoom: 4.,
+            fit_level: false,
+            edge_mode: EdgeMode::Wrap,
+            follow_speed: 8.,
+            focus: None,
         }
     }
 }
@@ -1,6 +1,7 @@
 use amethyst::core::ecs::HashMapStorage;
 use amethyst::{
     assets::PrefabData,
+    core::math::Vector2,
     core::transform::Transform,
     derive::PrefabData,
     ecs::{prelude::Entity, Component, DenseVecStorage, WriteStorage},
@@ -376,9 +377,18 @@ impl Steering {
     ///
     /// Note that this does NOT return a discrete position: output is not rounded or floored.
     pub fn to_anchor_coords(&self, transform: &Transform) -> (f32, f32) {
+        self.translation_to_anchor_coords(&Vector2::new(
+            transform.translation().x,
+            transform.translation().y,
+        ))
+    }
+
+    /// Same as `to_anchor_coords`, but takes a bare translation instead of a Transform.
+    /// This is what the headless simulation uses, as it does not keep any Transforms.
+    pub fn translation_to_anchor_coords(&self, translation: &Vector2<f32>) -> (f32, f32) {
         (
-            transform.translation().x - 0.5 * self.dimens.x as f32,
-            transform.translation().y - 0.5 * self.dimens.y as f32,
+            translation.x - 0.5 * self.dimens.x as f32,
+            translation.y - 0.5 * self.dimens.y as f32,
         )
     }
 }
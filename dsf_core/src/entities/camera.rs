@@ -8,6 +8,9 @@ use amethyst::{
     window::ScreenDimensions,
 };
 
+/// The scale of the camera frame at a zoom factor of 1.0. One meter in the world is 32 pixels wide.
+pub const CAMERA_SCALE: f32 = 1. / 32.;
+
 /// Initialise the camera.
 pub fn create_camera(world: &mut World) {
     let frame = initialise_camera_frame(world);
@@ -30,8 +33,7 @@ pub fn create_camera_under_parent(world: &mut World, parent: Entity) {
 pub fn initialise_camera_frame(world: &mut World) -> Entity {
     let mut transform = Transform::default();
     transform.set_translation_xyz(0.0, 0.0, (&DepthLayer::Camera).z());
-    let scale = 1. / 32.;
-    transform.set_scale(Vector3::new(scale, scale, 1.0));
+    transform.set_scale(Vector3::new(CAMERA_SCALE, CAMERA_SCALE, 1.0));
     world
         .create_entity()
         .with(CameraFrame::default())
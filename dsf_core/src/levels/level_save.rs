@@ -1,15 +1,29 @@
 use crate::components::Pos;
+use crate::levels::{LevelMetadata, CURRENT_LEVEL_VERSION};
 use crate::resources::WorldBounds;
 use serde::{Deserialize, Serialize, Serializer};
 use std::collections::{BTreeMap, HashMap};
 
+/// Names that levels cannot be saved under, because the editor uses them for its own files.
+/// Files with these names are not real levels and are skipped when scanning for levels.
+pub const RESERVED_LEVEL_NAMES: [&str; 1] = ["auto_save"];
+
 /// Describes a complete level. This is the format that the level is stored in.
 /// Contains a map of positions, mapped to tile definitions.
 /// This struct can be loaded from a level file and used to start a game.
-#[derive(Debug, Deserialize, Serialize, Default)]
+///
+/// Load level files through `load_level_save`, which upgrades files that use an older version of
+/// the format.
+#[derive(Debug, Deserialize, Serialize)]
 #[serde(default)]
 #[serde(deny_unknown_fields)]
 pub struct LevelSave {
+    /// The version of the level format. See CURRENT_LEVEL_VERSION.
+    pub version: u32,
+    /// Title, author and other information about the level. Levels that have none of it leave
+    /// this out of the file entirely.
+    #[serde(skip_serializing_if = "LevelMetadata::is_empty")]
+    pub metadata: LevelMetadata,
     /// The level's exterior borders. In this game, the world wraps at the borders.
     pub world_bounds: WorldBounds,
     /// Mapping of (x,y) position in the world to a TileDefinition key.
@@ -18,6 +32,17 @@ pub struct LevelSave {
     pub tiles: HashMap<Pos, String>,
 }
 
+impl Default for LevelSave {
+    fn default() -> Self {
+        LevelSave {
+            version: CURRENT_LEVEL_VERSION,
+            metadata: LevelMetadata::default(),
+            world_bounds: WorldBounds::default(),
+            tiles: HashMap::new(),
+        }
+    }
+}
+
 /// A function used by serde to serialise the tile map in a deterministic way.
 /// This will prevent the output being different each time the level is saved, which will
 /// prevent lots of unnecessarily large diffs in the git commits.
@@ -0,0 +1,379 @@
+use crate::components::Pos;
+use crate::levels::{LevelMetadata, LevelSave, CURRENT_LEVEL_VERSION};
+use crate::resources::{TileDefinitions, WorldBounds};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Levels whose file has this extension are stored in the text format. All other level files are
+/// RON files.
+///
+/// The text format draws the level as a grid of characters, which is easier to read, edit and
+/// review than the RON format. It looks like this:
+///
+/// ```text
+/// version: 1
+/// world_bounds: -6 -2 12 6
+/// title: Tiny
+/// legend:
+/// B Block1
+/// D Door
+/// K Key
+/// P Player
+/// grid: -6 3
+/// ............
+/// .++++.......
+/// .++++.......
+/// .++++++.++..
+/// .D+++K+.P+..
+/// BBBBBBBBBBBB
+/// ```
+///
+/// - The header holds the version, the world bounds (x, y, width, height) and any metadata. Each
+///   field is separated from its value by a colon and a single space. Everything after that is
+///   the value, including any leading or trailing whitespace. Line breaks, carriage returns and
+///   backslashes in the metadata are escaped as `\n`, `\r` and `\\`.
+/// - The legend maps each character in the grid to a tile key.
+/// - The grid line gives the position of the top-left character of the grid. The grid covers
+///   at least the world bounds.
+/// - Each tile is drawn at its anchor, which is its bottom-left cell. The other cells it covers are
+///   drawn as `+`. These are only there for the reader and are ignored when loading. Empty cells
+///   are drawn as `.`.
+pub const LEVEL_TEXT_EXTENSION: &str = "lvl";
+
+const EMPTY: char = '.';
+const COVERED: char = '+';
+/// Characters that can stand for a tile whose first letter is already taken by another tile.
+const SPARE_CHARS: &str =
+    "#=@%&*$!?~^<>/|0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Returns true if the file at the given path is a level in the text format.
+pub fn is_level_text_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext == LEVEL_TEXT_EXTENSION)
+        .unwrap_or(false)
+}
+
+/// Writes the level in the text format.
+///
+/// The tile definitions are used to mark the cells that multi-cell tiles cover.
+/// Fails if the level uses more different tiles than there are characters to draw them with.
+pub fn level_to_text(level: &LevelSave, tile_defs: &TileDefinitions) -> Result<String, String> {
+    let legend = assign_legend(level.tiles.values())?;
+    let (top_left, width, height) = grid_area(level, tile_defs);
+    let mut grid = vec![vec![EMPTY; width as usize]; height as usize];
+    let cell = |pos: &Pos| ((pos.x - top_left.x) as usize, (top_left.y - pos.y) as usize);
+    // First mark all covered cells, then draw the anchors. That way, an anchor is never hidden by
+    // a tile that overlaps it.
+    for (pos, key) in &level.tiles {
+        let dimens = tile_defs.get(key).dimens;
+        for x in pos.x..(pos.x + dimens.x) {
+            for y in pos.y..(pos.y + dimens.y) {
+                let (column, row) = cell(&Pos::new(x, y));
+                grid[row][column] = COVERED;
+            }
+        }
+    }
+    for (pos, key) in &level.tiles {
+        let (column, row) = cell(pos);
+        grid[row][column] = legend[key];
+    }
+
+    let mut lines = vec![
+        format!("version: {}", level.version),
+        format!(
+            "world_bounds: {} {} {} {}",
+            level.world_bounds.x(),
+            level.world_bounds.y(),
+            level.world_bounds.width(),
+            level.world_bounds.height()
+        ),
+    ];
+    lines.extend(metadata_lines(&level.metadata));
+    lines.push("legend:".to_string());
+    let mut legend = legend.iter().collect::<Vec<_>>();
+    legend.sort();
+    lines.extend(legend.iter().map(|(key, c)| format!("{} {}", c, key)));
+    lines.push(format!("grid: {} {}", top_left.x, top_left.y));
+    lines.extend(grid.iter().map(|row| row.iter().collect::<String>()));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Reads a level in the text format.
+/// Returns a message that describes the problem if the text is not a valid level.
+pub fn level_from_text(text: &str) -> Result<LevelSave, String> {
+    let mut level = LevelSave::default();
+    let mut lines = text.lines().enumerate();
+
+    // The header, up to and including the legend line.
+    loop {
+        let (line_nr, line) = lines
+            .next()
+            .ok_or_else(|| "The level has no legend.".to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if line.trim() == "legend:" {
+            break;
+        }
+        let (field, value) = split_field(line)
+            .ok_or_else(|| format!("Line {}: expected a field, found {:?}.", line_nr + 1, line))?;
+        read_header_field(&mut level, field, value)
+            .map_err(|message| format!("Line {}: {}", line_nr + 1, message))?;
+    }
+    if level.version > CURRENT_LEVEL_VERSION {
+        return Err(format!(
+            "Level format version {} is newer than the latest version this game knows, {}.",
+            level.version, CURRENT_LEVEL_VERSION
+        ));
+    }
+
+    // The legend, up to and including the grid line.
+    let mut legend = HashMap::new();
+    let top_left = loop {
+        let (line_nr, line) = lines
+            .next()
+            .ok_or_else(|| "The level has no grid.".to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(("grid", value)) = split_field(line.trim()) {
+            let numbers = parse_numbers(value, 2)
+                .map_err(|message| format!("Line {}: {}", line_nr + 1, message))?;
+            break Pos::new(numbers[0], numbers[1]);
+        }
+        let mut chars = line.chars();
+        let c = chars.next().expect("Line is not empty.");
+        let key = chars.as_str().trim();
+        if c == EMPTY || c == COVERED || c.is_whitespace() || key.is_empty() {
+            return Err(format!(
+                "Line {}: expected a character and a tile key, found {:?}.",
+                line_nr + 1,
+                line
+            ));
+        }
+        legend.insert(c, key.to_string());
+    };
+
+    // The grid, up to the end of the file.
+    for (row, (line_nr, line)) in lines.enumerate() {
+        for (column, c) in line.trim_end().chars().enumerate() {
+            if c == EMPTY || c == COVERED {
+                continue;
+            }
+            let key = legend.get(&c).ok_or_else(|| {
+                format!(
+                    "Line {}: character {:?} is not in the legend.",
+                    line_nr + 1,
+                    c
+                )
+            })?;
+            let pos = Pos::new(top_left.x + column as i32, top_left.y - row as i32);
+            level.tiles.insert(pos, key.clone());
+        }
+    }
+    Ok(level)
+}
+
+/// Picks a character for each tile key. Where possible, this is the first letter of the key.
+/// Keys are handled in alphabetical order, so the same set of keys always gets the same
+/// characters.
+fn assign_legend<'a>(
+    keys: impl Iterator<Item = &'a String>,
+) -> Result<HashMap<String, char>, String> {
+    let keys = keys.collect::<BTreeSet<_>>();
+    let mut legend = HashMap::new();
+    let mut used = BTreeSet::new();
+    for key in keys {
+        let first = key.chars().next().filter(|c| c.is_ascii_alphanumeric());
+        let c = first
+            .into_iter()
+            .chain(first.map(|c| c.to_ascii_lowercase()))
+            .chain(SPARE_CHARS.chars())
+            .find(|c| !used.contains(c))
+            .ok_or_else(|| "The level uses too many different tiles.".to_string())?;
+        used.insert(c);
+        legend.insert(key.clone(), c);
+    }
+    Ok(legend)
+}
+
+/// Returns the position of the top-left cell, the width and the height of the smallest grid that
+/// covers both the world bounds and all tiles.
+fn grid_area(level: &LevelSave, tile_defs: &TileDefinitions) -> (Pos, i32, i32) {
+    let bounds = &level.world_bounds;
+    let (mut min_x, mut min_y) = (bounds.x(), bounds.y());
+    let (mut max_x, mut max_y) = (bounds.upper_x(), bounds.upper_y());
+    for (pos, key) in &level.tiles {
+        let dimens = tile_defs.get(key).dimens;
+        min_x = min_x.min(pos.x);
+        min_y = min_y.min(pos.y);
+        max_x = max_x.max(pos.x + dimens.x);
+        max_y = max_y.max(pos.y + dimens.y);
+    }
+    (Pos::new(min_x, max_y - 1), max_x - min_x, max_y - min_y)
+}
+
+fn metadata_lines(metadata: &LevelMetadata) -> Vec<String> {
+    let mut lines = Vec::new();
+    let text_fields = [
+        ("title", &metadata.title),
+        ("author", &metadata.author),
+        ("description", &metadata.description),
+    ];
+    for (field, value) in text_fields.iter() {
+        if !value.is_empty() {
+            lines.push(format!("{}: {}", field, escape(value)));
+        }
+    }
+    if let Some(difficulty) = metadata.difficulty {
+        lines.push(format!("difficulty: {}", difficulty));
+    }
+    if !metadata.hint.is_empty() {
+        lines.push(format!("hint: {}", escape(&metadata.hint)));
+    }
+    if let Some(par) = metadata.par_moves {
+        lines.push(format!("par_moves: {}", par));
+    }
+    lines
+}
+
+fn read_header_field(level: &mut LevelSave, field: &str, value: &str) -> Result<(), String> {
+    let metadata = &mut level.metadata;
+    match field {
+        "version" => {
+            level.version = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("{:?} is not a version number.", value))?
+        }
+        "world_bounds" => {
+            let numbers = parse_numbers(value, 4)?;
+            level.world_bounds = WorldBounds::new(numbers[0], numbers[1], numbers[2], numbers[3]);
+        }
+        "title" => metadata.title = unescape(value),
+        "author" => metadata.author = unescape(value),
+        "description" => metadata.description = unescape(value),
+        "difficulty" => metadata.difficulty = Some(value.parse()?),
+        "hint" => metadata.hint = unescape(value),
+        "par_moves" => {
+            metadata.par_moves = Some(
+                value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("{:?} is not a number of moves.", value))?,
+            )
+        }
+        _ => return Err(format!("Unknown field {:?}.", field)),
+    }
+    Ok(())
+}
+
+/// Splits a line of the form `field: value`. Only the space that separates the field from the
+/// value is removed, so metadata can start or end with whitespace.
+fn split_field(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.splitn(2, ':');
+    let field = parts.next()?.trim();
+    let value = parts.next()?;
+    Some((field, value.strip_prefix(' ').unwrap_or(value)))
+}
+
+/// Parses exactly the given amount of whitespace-separated integers.
+fn parse_numbers(value: &str, amount: usize) -> Result<Vec<i32>, String> {
+    let numbers = value
+        .split_whitespace()
+        .map(|number| number.parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("{:?} is not a list of numbers.", value))?;
+    if numbers.len() == amount {
+        Ok(numbers)
+    } else {
+        Err(format!("Expected {} numbers, found {:?}.", amount, value))
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::{load_level_save, load_tile_definitions, Difficulty};
+    use crate::utility::files::get_levels_dir;
+
+    fn assert_round_trips(level: &LevelSave, tile_defs: &TileDefinitions) {
+        let text = level_to_text(level, tile_defs).expect("Failed to write level as text.");
+        let read = level_from_text(&text).expect("Failed to read level text.");
+        assert_eq!(read.version, level.version);
+        assert_eq!(read.world_bounds, level.world_bounds);
+        assert_eq!(read.metadata, level.metadata);
+        assert_eq!(read.tiles, level.tiles);
+        assert_eq!(level_to_text(&read, tile_defs).unwrap(), text);
+    }
+
+    #[test]
+    fn bundled_levels_round_trip() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        for name in &["demo_level.ron", "confined.ron"] {
+            let level = load_level_save(get_levels_dir().join(name))
+                .unwrap_or_else(|err| panic!("Failed to load {}: {:?}", name, err));
+            assert!(!level.tiles.is_empty());
+            assert_round_trips(&level, &tile_defs);
+        }
+    }
+
+    #[test]
+    fn metadata_round_trips_exactly() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let mut level = load_level_save(get_levels_dir().join("confined.ron")).unwrap();
+        level.metadata = LevelMetadata {
+            title: "  Padded title  ".to_string(),
+            author: "\tTabbed: with a colon".to_string(),
+            description: "Two lines,\nwith a \\ backslash\r\nand a trailing space ".to_string(),
+            difficulty: Some(Difficulty::Hard),
+            hint: " ".to_string(),
+            par_moves: Some(12),
+        };
+        assert_round_trips(&level, &tile_defs);
+    }
+
+    #[test]
+    fn header_values_are_separated_by_a_single_space() {
+        let text = "version: 1 \n\
+                    world_bounds:  0 0 2 1\n\
+                    title:  Two spaces\n\
+                    par_moves:7\n\
+                    legend:\n\
+                    B Block1\n\
+                    grid: 0 0\n\
+                    B.\n";
+        let level = level_from_text(text).expect("Failed to read level text.");
+        assert_eq!(level.version, 1);
+        assert_eq!(level.world_bounds, WorldBounds::new(0, 0, 2, 1));
+        assert_eq!(level.metadata.title, " Two spaces");
+        assert_eq!(level.metadata.par_moves, Some(7));
+        assert_eq!(level.tiles[&Pos::new(0, 0)], "Block1");
+        assert_eq!(level.tiles.len(), 1);
+    }
+}
@@ -12,7 +12,7 @@ use dsf_precompile::MyPrefabData;
 
 use crate::components::*;
 
-use crate::levels::LevelSave;
+use crate::levels::{load_level_save, validate};
 use crate::resources::*;
 
 use crate::utility::files::get_world_dir;
@@ -28,69 +28,161 @@ pub fn load_level(level_file: &PathBuf, world: &mut World) -> Result<(), ConfigE
     let mut win_condition = WinCondition::default();
     let display_debug_frames = world.read_resource::<DebugSettings>().display_debug_frames;
     let tile_defs = load_tile_definitions()?;
-    let level = LevelSave::load(level_file)?;
+    let level = load_level_save(level_file)?;
+    validate(&level, &tile_defs).iter().for_each(|issue| {
+        error!("Level {:?} has an issue: {}", level_file, issue);
+    });
     add_background(world, &level.world_bounds);
     level.tiles.iter().for_each(|(pos, tile_def_key)| {
         let tile_def = tile_defs.get(tile_def_key);
+        match tile_def.archetype {
+            Some(Archetype::Player) if display_debug_frames => build_frames(world, tile_def),
+            Some(Archetype::Key) => win_condition.add_key(pos),
+            _ => (),
+        }
+        build_tile(world, pos, tile_def_key, tile_def);
+        if tile_def.archetype.is_none() {
+            add_wrapped_copies(world, pos, tile_def, &level.world_bounds);
+        }
+    });
+    add_key_displays_to_door(world, &win_condition);
+    world.insert(win_condition);
+    world.insert(History::new(level.tiles.clone()));
+    world.insert(TileMap::for_play(level, tile_defs));
+    Ok(())
+}
+
+/// Rebuilds the entities of a level tile that was removed during play, such as a broken block,
+/// a collected key or a picked up tool. Used when rewinding.
+///
+/// Only rebuilds the entities, the TileMap and WinCondition must be restored separately.
+pub fn restore_tile(world: &mut World, pos: &Pos, tile_def_key: &str) {
+    let (tile_def, world_bounds) = {
+        let tile_map = world.read_resource::<TileMap>();
+        (
+            tile_map.tile_defs.get(tile_def_key).clone(),
+            tile_map.world_bounds.clone(),
+        )
+    };
+    build_tile(world, pos, tile_def_key, &tile_def);
+    match tile_def.archetype {
+        None => add_wrapped_copies(world, pos, &tile_def, &world_bounds),
+        Some(Archetype::Key) => {
+            let slot = level_keys(world).iter().position(|key| key == pos);
+            if let (Some(door_entity), Some(slot)) = (find_door(world), slot) {
+                add_key_display(world, door_entity, pos, slot);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Creates the entity for a single tile in the level.
+fn build_tile(world: &mut World, pos: &Pos, tile_def_key: &str, tile_def: &TileDefinition) {
+    let still_asset = load_still_asset(tile_def, &world.read_resource::<Assets>());
+    let anim_asset = load_anim_asset(tile_def, &world.read_resource::<Assets>());
+    let transform = if let Some(asset) = &tile_def.asset {
+        Some(load_transform(
+            &pos,
+            &tile_def.depth,
+            &tile_def.dimens,
+            asset,
+        ))
+    } else {
+        None
+    };
+    let mut builder = world.create_entity();
+    if let Some(still_asset) = still_asset {
+        builder = builder.with(still_asset);
+    }
+    if let Some(anim_asset) = anim_asset {
+        builder = builder.with(anim_asset);
+    }
+    if let Some(transform) = transform {
+        builder = builder.with(transform);
+    }
+    builder = builder.with(Block { pos: *pos });
+    match tile_def.archetype {
+        Some(Archetype::Player) => {
+            let _ = build_player(builder, pos, tile_def);
+        }
+        Some(Archetype::Key) => {
+            builder.with(Key::new(*pos)).build();
+        }
+        Some(Archetype::Tool(tool_type)) => {
+            if let Some(AssetType::Still(sprite, sprite_nr)) = tile_def.asset {
+                builder
+                    .with(Tool::new(tool_type, sprite, sprite_nr))
+                    .build();
+            } else {
+                error!(
+                    "Tool definition {:?} did not have still asset.",
+                    tile_def_key
+                );
+            }
+        }
+        Some(Archetype::Door) => {
+            builder.with(ExitDoor).build();
+        }
+        _ => {
+            builder.build();
+        }
+    };
+}
+
+/// How far past the edges of the level the wrapped copies of the tiles extend, in meters.
+const WRAP_MARGIN: i32 = 16;
+
+/// Levels wrap around. To show this, draw copies of the tiles near each edge of the level just
+/// outside the opposite edge.
+///
+/// Only call this for tiles without an archetype. Keys, tools, doors and the player change during
+/// play, so their copies would get out of sync. Each copy carries the Block of the tile it copies,
+/// so it is destroyed along with the original.
+fn add_wrapped_copies(
+    world: &mut World,
+    pos: &Pos,
+    tile_def: &TileDefinition,
+    bounds: &WorldBounds,
+) {
+    let asset = match &tile_def.asset {
+        Some(asset) => asset,
+        None => return,
+    };
+    let offsets = (-1..=1)
+        .flat_map(|x| (-1..=1).map(move |y| (x, y)))
+        .filter(|offset| *offset != (0, 0))
+        .map(|(x, y)| Pos::new(x * bounds.width(), y * bounds.height()));
+    for offset in offsets {
+        let copy_pos = Pos::new(pos.x + offset.x, pos.y + offset.y);
+        if !within_wrap_margin(&copy_pos, &tile_def.dimens, bounds) {
+            continue;
+        }
         let still_asset = load_still_asset(tile_def, &world.read_resource::<Assets>());
         let anim_asset = load_anim_asset(tile_def, &world.read_resource::<Assets>());
-        let transform = if let Some(asset) = &tile_def.asset {
-            Some(load_transform(
-                &pos,
-                &tile_def.depth,
-                &tile_def.dimens,
-                asset,
-            ))
-        } else {
-            None
-        };
-        let mut builder = world.create_entity();
+        let mut builder = world.create_entity().with(load_transform(
+            &copy_pos,
+            &tile_def.depth,
+            &tile_def.dimens,
+            asset,
+        ));
         if let Some(still_asset) = still_asset {
             builder = builder.with(still_asset);
         }
         if let Some(anim_asset) = anim_asset {
             builder = builder.with(anim_asset);
         }
-        if let Some(transform) = transform {
-            builder = builder.with(transform);
-        }
-        builder = builder.with(Block { pos: *pos });
-        match tile_def.archetype {
-            Some(Archetype::Player) => {
-                let _ = build_player(builder, pos, tile_def);
-                if display_debug_frames {
-                    build_frames(world, tile_def);
-                }
-            }
-            Some(Archetype::Key) => {
-                win_condition.add_key(pos);
-                builder.with(Key::new(*pos)).build();
-            }
-            Some(Archetype::Tool(tool_type)) => {
-                if let Some(AssetType::Still(sprite, sprite_nr)) = tile_def.asset {
-                    builder
-                        .with(Tool::new(tool_type, sprite, sprite_nr))
-                        .build();
-                } else {
-                    error!(
-                        "Tool definition {:?} did not have still asset.",
-                        tile_def_key
-                    );
-                }
-            }
-            Some(Archetype::Door) => {
-                builder.with(ExitDoor).build();
-            }
-            _ => {
-                builder.build();
-            }
-        };
-    });
-    add_key_displays_to_door(world, &win_condition);
-    world.insert(win_condition);
-    world.insert(TileMap::for_play(level, tile_defs));
-    world.insert(History::default());
-    Ok(())
+        builder.with(Block { pos: *pos }).build();
+    }
+}
+
+/// Returns true if a tile with the given position and dimensions overlaps the area just outside
+/// the world bounds where the wrapped copies are drawn.
+fn within_wrap_margin(pos: &Pos, dimens: &Pos, bounds: &WorldBounds) -> bool {
+    pos.x + dimens.x > bounds.x() - WRAP_MARGIN
+        && pos.x < bounds.upper_x() + WRAP_MARGIN
+        && pos.y + dimens.y > bounds.y() - WRAP_MARGIN
+        && pos.y < bounds.upper_y() + WRAP_MARGIN
 }
 
 fn build_player(builder: EntityBuilder, pos: &Pos, tile_def: &TileDefinition) -> Entity {
@@ -120,55 +212,80 @@ pub fn add_background(world: &mut World, world_bounds: &WorldBounds) {
 }
 
 fn add_key_displays_to_door(world: &mut World, win_condition: &WinCondition) {
-    let door_entity = world.exec(|(doors, entities): (ReadStorage<ExitDoor>, Entities)| {
+    if let Some(door_entity) = find_door(world) {
+        // Sort the keys, so that each key always gets the same spot on the door. A key that is
+        // restored by rewinding then ends up where it was before.
+        let mut keys = win_condition.keys.iter().collect::<Vec<_>>();
+        keys.sort();
+        keys.iter()
+            .enumerate()
+            .for_each(|(slot, key)| add_key_display(world, door_entity, key, slot));
+    }
+}
+
+fn find_door(world: &mut World) -> Option<Entity> {
+    world.exec(|(doors, entities): (ReadStorage<ExitDoor>, Entities)| {
         (&doors, &entities)
             .join()
             .map(|(_, entity)| (entity))
             .next()
-    });
-    if let Some(door_entity) = door_entity {
-        win_condition
-            .keys
-            .iter()
-            .enumerate()
-            .for_each(|(index, key)| {
-                // Temporary bit of code to arrange the key displays on the door in a
-                // visually pleasing manner. Rewrite this later, when we know exactly what we
-                // want to do with the door.
-                let i = if index < 2 {
-                    index + 5
-                } else if index < 4 {
-                    index + 7
-                } else if index < 5 {
-                    index
-                } else if index < 7 {
-                    index - 5
-                } else if index < 9 {
-                    index
-                } else if index < 11 {
-                    index - 7
-                } else {
-                    index
-                };
-                let mut transform = Transform::default();
-                let x_offset = i % 4;
-                let y_offset = i / 4;
-                transform.set_translation_x((-1.5 + x_offset as f32) * 64.);
-                transform.set_translation_y((-1.5 + y_offset as f32) * 64.);
-                transform.set_translation_z(1.); //One higher than parent.
-                transform.set_scale(Vector3::new(0.5, 0.5, 1.0));
-                let sprite = load_asset_from_world(&SpriteType::Blocks, 3, world);
-                world
-                    .create_entity()
-                    .with(Parent {
-                        entity: door_entity,
-                    })
-                    .with(transform)
-                    .with(sprite)
-                    .with(KeyDisplay::new(*key))
-                    .build();
-            });
-    }
+    })
+}
+
+/// The positions of all keys in the level as it was loaded, sorted.
+fn level_keys(world: &World) -> Vec<Pos> {
+    let history = world.read_resource::<History>();
+    let tile_map = world.read_resource::<TileMap>();
+    let mut keys = history
+        .level_tiles()
+        .iter()
+        .filter(|(_, tile_def_key)| {
+            tile_map.tile_defs.get(tile_def_key).archetype == Some(Archetype::Key)
+        })
+        .map(|(pos, _)| *pos)
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+/// Adds a miniature version of the key at the given position to the exit door. Each key has its
+/// own slot on the door.
+fn add_key_display(world: &mut World, door_entity: Entity, key: &Pos, slot: usize) {
+    // Temporary bit of code to arrange the key displays on the door in a
+    // visually pleasing manner. Rewrite this later, when we know exactly what we
+    // want to do with the door.
+    let i = if slot < 2 {
+        slot + 5
+    } else if slot < 4 {
+        slot + 7
+    } else if slot < 5 {
+        slot
+    } else if slot < 7 {
+        slot - 5
+    } else if slot < 9 {
+        slot
+    } else if slot < 11 {
+        slot - 7
+    } else {
+        slot
+    };
+    let mut transform = Transform::default();
+    let x_offset = i % 4;
+    let y_offset = i / 4;
+    transform.set_translation_x((-1.5 + x_offset as f32) * 64.);
+    transform.set_translation_y((-1.5 + y_offset as f32) * 64.);
+    transform.set_translation_z(1.); //One higher than parent.
+    transform.set_scale(Vector3::new(0.5, 0.5, 1.0));
+    let sprite = load_asset_from_world(&SpriteType::Blocks, 3, world);
+    world
+        .create_entity()
+        .with(Parent {
+            entity: door_entity,
+        })
+        .with(transform)
+        .with(sprite)
+        .with(KeyDisplay::new(*key))
+        .build();
 }
 
 fn build_frames(world: &mut World, tile_def: &TileDefinition) {
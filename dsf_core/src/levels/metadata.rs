@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Optional information about a level, for display in the level select screen.
+/// All fields are optional, levels without metadata are still valid.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct LevelMetadata {
+    /// The name of the level as shown to the player. If empty, the file name is shown instead.
+    pub title: String,
+    pub author: String,
+    pub description: String,
+    pub difficulty: Option<Difficulty>,
+    /// A nudge in the right direction for players who are stuck.
+    pub hint: String,
+    /// The number of moves in which the level designer managed to beat the level.
+    pub par_moves: Option<u32>,
+}
+
+impl LevelMetadata {
+    /// Returns true if none of the fields were filled in.
+    pub fn is_empty(&self) -> bool {
+        *self == LevelMetadata::default()
+    }
+
+    /// Returns the title of the level, or the given fallback if the level has no title.
+    pub fn title_or<'a>(&'a self, fallback: &'a str) -> &'a str {
+        if self.title.is_empty() {
+            fallback
+        } else {
+            &self.title
+        }
+    }
+}
+
+/// How hard a level is, according to its designer.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Parses the name of a difficulty, ignoring case.
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("Unknown difficulty {:?}.", s))
+    }
+}
@@ -0,0 +1,180 @@
+use crate::components::Pos;
+use crate::levels::{is_level_text_file, level_from_text, level_to_text, LevelMetadata, LevelSave};
+use crate::resources::{TileDefinitions, WorldBounds};
+use amethyst::config::{Config, ConfigError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The version of the level format that the game currently reads and writes.
+///
+/// To change the format of LevelSave:
+/// 1. Copy the current LevelSave into a new, frozen LevelSaveV{n} struct in this module.
+/// 2. Make your changes to LevelSave and bump this version.
+/// 3. Implement From<LevelSaveV{n}> for LevelSave and update the From implementation of the
+///    previous version to produce a LevelSaveV{n} instead.
+/// 4. Add a match arm to `load_and_migrate`.
+pub const CURRENT_LEVEL_VERSION: u32 = 1;
+
+/// Reads only the version of a level file and ignores everything else in it.
+#[derive(Debug, Deserialize, Serialize)]
+struct VersionProbe {
+    /// Level files from before the format was versioned do not have a version. They are version 0.
+    #[serde(default)]
+    version: u32,
+}
+
+/// Version 0 of the level format. This is the format from before levels had a version.
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+struct LevelSaveV0 {
+    metadata: LevelMetadata,
+    world_bounds: WorldBounds,
+    tiles: HashMap<Pos, String>,
+}
+
+/// Version 1 only added the version field.
+impl From<LevelSaveV0> for LevelSave {
+    fn from(level: LevelSaveV0) -> Self {
+        LevelSave {
+            version: 1,
+            metadata: level.metadata,
+            world_bounds: level.world_bounds,
+            tiles: level.tiles,
+        }
+    }
+}
+
+/// Loads the level file at the given path. If the file uses an older version of the level format,
+/// it is upgraded to the current version in memory. The file itself is left untouched.
+/// Files with the LEVEL_TEXT_EXTENSION are read in the text format, all others as RON.
+///
+/// Always use this instead of `LevelSave::load`, which only understands the current version of
+/// the RON format.
+pub fn load_level_save<P: AsRef<Path>>(path: P) -> Result<LevelSave, ConfigError> {
+    load_and_migrate(path.as_ref()).map(|(level, _)| level)
+}
+
+/// Writes the level to the given file. If the file has the LEVEL_TEXT_EXTENSION, the level is
+/// written in the text format, otherwise it is written as RON.
+pub fn save_level_file<P: AsRef<Path>>(
+    path: P,
+    level: &LevelSave,
+    tile_defs: &TileDefinitions,
+) -> Result<(), ConfigError> {
+    if is_level_text_file(path.as_ref()) {
+        let text = level_to_text(level, tile_defs).map_err(invalid_data)?;
+        fs::write(path, text).map_err(ConfigError::File)
+    } else {
+        level.write(path)
+    }
+}
+
+/// Upgrades the level file at the given path to the current version of the level format and
+/// writes it back to the same file, in the same format. Files that are already up to date are
+/// left untouched.
+///
+/// Returns the version that the file had before the upgrade.
+pub fn migrate_level_file<P: AsRef<Path>>(
+    path: P,
+    tile_defs: &TileDefinitions,
+) -> Result<u32, ConfigError> {
+    let (level, version) = load_and_migrate(path.as_ref())?;
+    if version < CURRENT_LEVEL_VERSION {
+        save_level_file(path, &level, tile_defs)?;
+    }
+    Ok(version)
+}
+
+/// Loads the level file at the given path and upgrades it to the current version.
+/// Returns the level, as well as the version the file was written in.
+fn load_and_migrate(path: &Path) -> Result<(LevelSave, u32), ConfigError> {
+    if is_level_text_file(path) {
+        // The text format maps directly onto the current LevelSave, it only needs a new version.
+        let text = fs::read_to_string(path).map_err(ConfigError::File)?;
+        let mut level = level_from_text(&text).map_err(invalid_data)?;
+        let version = level.version;
+        level.version = CURRENT_LEVEL_VERSION;
+        return Ok((level, version));
+    }
+    let version = VersionProbe::load(path)?.version;
+    let level = match version {
+        0 => LevelSave::from(LevelSaveV0::load(path)?),
+        CURRENT_LEVEL_VERSION => LevelSave::load(path)?,
+        _ => {
+            return Err(invalid_data(format!(
+                "Level format version {} is newer than the latest version this game knows, {}.",
+                version, CURRENT_LEVEL_VERSION
+            )))
+        }
+    };
+    Ok((level, version))
+}
+
+/// ConfigError has no variant for files that were read fine but whose contents make no sense.
+fn invalid_data(message: String) -> ConfigError {
+    ConfigError::File(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Writes the given contents to a file in a fresh temporary directory.
+    fn level_file(name: &str, contents: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("dsf_migrate_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).expect("Failed to create temporary directory.");
+        let file = dir.join("level.ron");
+        fs::write(&file, contents).expect("Failed to write level file.");
+        file
+    }
+
+    const VERSION_0: &str = r#"(
+    world_bounds: (pos: (x: 0, y: 0), dimens: (x: 10, y: 8)),
+    tiles: {
+        (x: 1, y: 0): "Player",
+        (x: 5, y: 0): "Door",
+    },
+)"#;
+
+    #[test]
+    fn levels_without_a_version_are_upgraded_on_load() {
+        let file = level_file("load", VERSION_0);
+        let level = load_level_save(&file).expect("Failed to load level.");
+        assert_eq!(level.version, CURRENT_LEVEL_VERSION);
+        assert_eq!(level.world_bounds, WorldBounds::new(0, 0, 10, 8));
+        assert_eq!(level.tiles[&Pos::new(1, 0)], "Player");
+        assert_eq!(level.tiles[&Pos::new(5, 0)], "Door");
+        // Loading never touches the file itself.
+        assert_eq!(fs::read_to_string(&file).unwrap(), VERSION_0);
+    }
+
+    #[test]
+    fn migrating_rewrites_the_file_once() {
+        let file = level_file("migrate", VERSION_0);
+        let tile_defs = TileDefinitions::default();
+        assert_eq!(migrate_level_file(&file, &tile_defs).unwrap(), 0);
+        let migrated = fs::read_to_string(&file).unwrap();
+        assert_ne!(migrated, VERSION_0);
+        assert_eq!(
+            migrate_level_file(&file, &tile_defs).unwrap(),
+            CURRENT_LEVEL_VERSION
+        );
+        assert_eq!(fs::read_to_string(&file).unwrap(), migrated);
+        let level = load_level_save(&file).unwrap();
+        assert_eq!(level.tiles.len(), 2);
+    }
+
+    #[test]
+    fn levels_from_a_newer_version_are_refused() {
+        let file = level_file(
+            "newer",
+            &format!("(version: {}, tiles: {{}})", CURRENT_LEVEL_VERSION + 1),
+        );
+        assert!(load_level_save(&file).is_err());
+    }
+}
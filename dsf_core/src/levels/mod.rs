@@ -1,5 +1,13 @@
 mod level_save;
+mod level_text;
 mod load;
+mod metadata;
+mod migrate;
+mod validate;
 
 pub use self::level_save::*;
+pub use self::level_text::*;
 pub use self::load::*;
+pub use self::metadata::*;
+pub use self::migrate::*;
+pub use self::validate::*;
@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::components::Pos;
+use crate::levels::LevelSave;
+use crate::resources::{Archetype, TileDefinition, TileDefinitions};
+
+/// A problem with a level that would make it unplayable or behave unexpectedly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelIssue {
+    /// The level does not contain a tile with this definition, but the definition is mandatory.
+    MissingMandatory { tile_def_key: String },
+    /// The level contains more than one tile with this definition, but the definition is unique.
+    DuplicateUnique {
+        tile_def_key: String,
+        positions: Vec<Pos>,
+    },
+    /// The tile definition does not exist. The fallback definition would be used instead.
+    UnknownTileDefinition { tile_def_key: String, pos: Pos },
+    /// The tile lies (partially) outside the world bounds.
+    OutOfBounds { tile_def_key: String, pos: Pos },
+    /// The tiles anchored at these two positions overlap.
+    Overlap { first: Pos, second: Pos },
+    /// The level does not contain any keys, so the exit door would never open.
+    NoKeys,
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelIssue::MissingMandatory { tile_def_key } => {
+                write!(f, "mandatory tile {:?} is missing", tile_def_key)
+            }
+            LevelIssue::DuplicateUnique {
+                tile_def_key,
+                positions,
+            } => write!(
+                f,
+                "unique tile {:?} appears {} times, at {:?}",
+                tile_def_key,
+                positions.len(),
+                positions
+            ),
+            LevelIssue::UnknownTileDefinition { tile_def_key, pos } => {
+                write!(f, "unknown tile definition {:?} at {:?}", tile_def_key, pos)
+            }
+            LevelIssue::OutOfBounds { tile_def_key, pos } => write!(
+                f,
+                "tile {:?} at {:?} is outside the world bounds",
+                tile_def_key, pos
+            ),
+            LevelIssue::Overlap { first, second } => {
+                write!(f, "tiles at {:?} and {:?} overlap", first, second)
+            }
+            LevelIssue::NoKeys => write!(f, "level has no keys"),
+        }
+    }
+}
+
+/// Checks the structure of the level against the tile definitions.
+/// Returns all issues found, in a deterministic order. If the list is empty, the level is sound.
+pub fn validate(level: &LevelSave, tile_defs: &TileDefinitions) -> Vec<LevelIssue> {
+    let mut issues = Vec::new();
+    // Sort the tiles, so the issues are always reported in the same order.
+    let mut tiles = level.tiles.iter().collect::<Vec<_>>();
+    tiles.sort_by_key(|(pos, _)| **pos);
+
+    let fallback = TileDefinition::fallback();
+    let mut positions_per_key: HashMap<&String, Vec<Pos>> = HashMap::new();
+    let mut occupied: HashMap<Pos, Pos> = HashMap::new();
+    let mut overlaps = HashSet::new();
+    let mut nr_keys = 0;
+    for (pos, tile_def_key) in tiles {
+        let tile_def = tile_defs.map.get(tile_def_key).unwrap_or_else(|| {
+            issues.push(LevelIssue::UnknownTileDefinition {
+                tile_def_key: tile_def_key.clone(),
+                pos: *pos,
+            });
+            &fallback
+        });
+        positions_per_key
+            .entry(tile_def_key)
+            .or_default()
+            .push(*pos);
+        if tile_def.archetype == Some(Archetype::Key) {
+            nr_keys += 1;
+        }
+        if !level.world_bounds.encloses(pos, &tile_def.dimens) {
+            issues.push(LevelIssue::OutOfBounds {
+                tile_def_key: tile_def_key.clone(),
+                pos: *pos,
+            });
+        }
+        for x in 0..tile_def.dimens.x {
+            for y in 0..tile_def.dimens.y {
+                if let Some(other) = occupied.insert(pos.append_xy(x, y), *pos) {
+                    if overlaps.insert((other, *pos)) {
+                        issues.push(LevelIssue::Overlap {
+                            first: other,
+                            second: *pos,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let mut tile_def_keys = tile_defs.map.keys().collect::<Vec<_>>();
+    tile_def_keys.sort();
+    for tile_def_key in tile_def_keys {
+        let tile_def = &tile_defs.map[tile_def_key];
+        let positions = positions_per_key.remove(tile_def_key).unwrap_or_default();
+        if tile_def.mandatory && positions.is_empty() {
+            issues.push(LevelIssue::MissingMandatory {
+                tile_def_key: tile_def_key.clone(),
+            });
+        }
+        if tile_def.unique && positions.len() > 1 {
+            issues.push(LevelIssue::DuplicateUnique {
+                tile_def_key: tile_def_key.clone(),
+                positions,
+            });
+        }
+    }
+    if nr_keys == 0 {
+        issues.push(LevelIssue::NoKeys);
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::load_tile_definitions;
+    use crate::resources::WorldBounds;
+
+    /// A level that has no issues: a player, an exit door and a key.
+    const SOUND: [(i32, i32, &str); 3] = [(0, 0, "Player"), (4, 0, "Door"), (10, 0, "Key")];
+
+    fn level(tiles: &[(i32, i32, &str)]) -> LevelSave {
+        LevelSave {
+            world_bounds: WorldBounds::new(0, 0, 14, 6),
+            tiles: tiles
+                .iter()
+                .map(|(x, y, key)| (Pos::new(*x, *y), key.to_string()))
+                .collect(),
+            ..LevelSave::default()
+        }
+    }
+
+    /// The sound level with the given tiles added to it.
+    fn sound_level_with(extra: &[(i32, i32, &str)]) -> LevelSave {
+        level(&[&SOUND[..], extra].concat())
+    }
+
+    #[test]
+    fn sound_level_has_no_issues() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        assert_eq!(validate(&level(&SOUND), &tile_defs), vec![]);
+    }
+
+    #[test]
+    fn every_issue_is_reported() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let cases = vec![
+            (
+                level(&[(0, 0, "Player"), (10, 0, "Key")]),
+                LevelIssue::MissingMandatory {
+                    tile_def_key: "Door".to_string(),
+                },
+            ),
+            (
+                sound_level_with(&[(0, 3, "Player")]),
+                LevelIssue::DuplicateUnique {
+                    tile_def_key: "Player".to_string(),
+                    positions: vec![Pos::new(0, 0), Pos::new(0, 3)],
+                },
+            ),
+            (
+                sound_level_with(&[(12, 0, "Lava")]),
+                LevelIssue::UnknownTileDefinition {
+                    tile_def_key: "Lava".to_string(),
+                    pos: Pos::new(12, 0),
+                },
+            ),
+            (
+                sound_level_with(&[(14, 0, "Block1")]),
+                LevelIssue::OutOfBounds {
+                    tile_def_key: "Block1".to_string(),
+                    pos: Pos::new(14, 0),
+                },
+            ),
+            (
+                sound_level_with(&[(1, 1, "Block1")]),
+                LevelIssue::Overlap {
+                    first: Pos::new(0, 0),
+                    second: Pos::new(1, 1),
+                },
+            ),
+            (
+                level(&[(0, 0, "Player"), (4, 0, "Door")]),
+                LevelIssue::NoKeys,
+            ),
+        ];
+        for (level, issue) in cases {
+            assert_eq!(validate(&level, &tile_defs), vec![issue]);
+        }
+    }
+}
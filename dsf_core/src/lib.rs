@@ -9,6 +9,7 @@ pub mod components;
 pub mod entities;
 pub mod levels;
 pub mod resources;
+pub mod sim;
 pub mod states;
 pub mod systems;
 pub mod utility;
@@ -7,13 +7,13 @@ use amethyst::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::components::*;
-use crate::levels::{load_asset_from_world, load_transform, LevelSave};
-use crate::resources::{AssetType, DepthLayer, SpriteType, UserCache};
+use crate::levels::{load_asset_from_world, load_level_save, load_transform, LevelMetadata};
+use crate::resources::{AssetType, DepthLayer, SaveGame, SpriteType, UserCache};
 use crate::utility::files::{get_adventures_dir, get_levels_dir};
 use amethyst::config::ConfigError;
 use amethyst::renderer::palette::Srgba;
 use amethyst::renderer::resources::Tint;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct PositionOnMap {
@@ -30,6 +30,94 @@ impl PositionOnMap {
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Adventure {
     pub(crate) nodes: HashMap<Pos, MapElement>,
+    /// The positions on the map that the player can currently move their cursor to. This depends
+    /// on the player's progress, so it is worked out when the adventure is loaded.
+    #[serde(skip)]
+    reachable: HashSet<Pos>,
+}
+
+impl Adventure {
+    pub fn is_reachable(&self, pos: &Pos) -> bool {
+        self.reachable.contains(pos)
+    }
+
+    /// Works out which positions on the map the player can reach, given their progress.
+    ///
+    /// Starting at (0, 0), the player can move along roads and nodes, except:
+    /// - They cannot enter a node whose unlock condition has not been met.
+    /// - They can enter a blocking node, but cannot move past it until they have beaten it.
+    pub fn update_reachable(&mut self, save_game: &SaveGame) {
+        let nr_levels_beaten = self
+            .nodes
+            .values()
+            .filter(|map_element| is_beaten(map_element, save_game))
+            .count();
+        let is_open = |pos: &Pos| match self.nodes.get(pos) {
+            Some(MapElement::Road) => true,
+            Some(MapElement::Node(node)) => node
+                .unlock
+                .as_ref()
+                .map(|unlock| unlock.is_met(nr_levels_beaten, save_game))
+                .unwrap_or(true),
+            None => false,
+        };
+        let mut reachable = HashSet::new();
+        let mut to_visit = vec![Pos::default()];
+        while let Some(pos) = to_visit.pop() {
+            if reachable.contains(&pos) || !is_open(&pos) {
+                continue;
+            }
+            reachable.insert(pos);
+            let blocks_the_way = match self.nodes.get(&pos) {
+                Some(map_element @ MapElement::Node(AdventureNode { blocking: true, .. })) => {
+                    !is_beaten(map_element, save_game)
+                }
+                _ => false,
+            };
+            if !blocks_the_way {
+                to_visit.extend(
+                    [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                        .iter()
+                        .map(|(x, y)| pos.append_xy(*x, *y)),
+                );
+            }
+        }
+        self.reachable = reachable;
+    }
+
+    /// Only levels can be beaten, so a node that leads to another adventure would block the way
+    /// for good. Such nodes are made non-blocking instead.
+    /// Returns the positions of the nodes that were changed, so they can be reported.
+    fn unblock_adventure_nodes(&mut self) -> Vec<Pos> {
+        let mut changed = self
+            .nodes
+            .iter_mut()
+            .filter_map(|(pos, map_element)| match map_element {
+                MapElement::Node(node @ AdventureNode { blocking: true, .. }) => {
+                    if let NodeDetails::Adventure(_) = node.details {
+                        node.blocking = false;
+                        Some(*pos)
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        changed.sort();
+        changed
+    }
+}
+
+/// Only levels can be beaten. A level is beaten once the player has completed it.
+fn is_beaten(map_element: &MapElement, save_game: &SaveGame) -> bool {
+    match map_element {
+        MapElement::Node(AdventureNode {
+            details: NodeDetails::Level(level_name),
+            ..
+        }) => save_game.is_completed(level_name),
+        _ => false,
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -43,10 +131,22 @@ pub struct AdventureNode {
     pub name: String,
     // pub description: String,
     pub details: NodeDetails,
-    // If true, the player must defeat this node before they can move further.
-    // If false, nodes behind this node are reachable and playable even if this node was never
-    // entered.
-    // pub blocking: bool,
+    /// If true, the player must defeat this node before they can move further.
+    /// If false, nodes behind this node are reachable and playable even if this node was never
+    /// entered.
+    ///
+    /// Only levels can be defeated. Adventure nodes therefore cannot block; if they are marked as
+    /// blocking anyway, a warning is logged when the adventure is loaded and the flag is ignored.
+    #[serde(default)]
+    pub blocking: bool,
+    /// If set, the player cannot enter this node until they meet this condition.
+    #[serde(default)]
+    pub unlock: Option<UnlockCondition>,
+    /// Title, description and other information about the level behind this node. Is read from
+    /// the level file when the adventure is loaded, so it is not stored in the adventure file.
+    /// Always None for nodes that are not levels.
+    #[serde(skip)]
+    pub metadata: Option<LevelMetadata>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -58,6 +158,27 @@ pub enum NodeDetails {
     Level(String),
 }
 
+/// A condition that the player must meet before they can enter a node on the map.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum UnlockCondition {
+    /// The player must have beaten at least this many of the levels in this adventure.
+    LevelsBeaten(usize),
+    /// The player must have beaten the level with this file name. The level does not have to be
+    /// part of this adventure.
+    LevelBeaten(String),
+}
+
+impl UnlockCondition {
+    /// Returns true if the player meets this condition. Takes the number of levels in the
+    /// adventure that the player has beaten, and the player's progress.
+    pub fn is_met(&self, nr_levels_beaten: usize, save_game: &SaveGame) -> bool {
+        match self {
+            UnlockCondition::LevelsBeaten(nr_levels) => nr_levels_beaten >= *nr_levels,
+            UnlockCondition::LevelBeaten(level_name) => save_game.is_completed(level_name),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Default)]
 pub struct Road {
     pub start_id: u16,
@@ -72,7 +193,7 @@ pub fn create_default_adventure() {
         .iter()
         .map(|level_name| {
             let level_file = get_levels_dir().join(level_name);
-            (level_name, LevelSave::load(level_file))
+            (level_name, load_level_save(level_file))
         })
         .filter(|(level_name, result)| {
             result
@@ -91,6 +212,9 @@ pub fn create_default_adventure() {
                 MapElement::Node(AdventureNode {
                     name: level_name.clone(),
                     details: NodeDetails::Level(level_name.clone()),
+                    blocking: false,
+                    unlock: None,
+                    metadata: None,
                 }),
             );
             if index > 0 {
@@ -132,16 +256,26 @@ fn level_files() -> Vec<String> {
 }
 
 pub fn load_adventure(path: &PathBuf, world: &mut World) -> Result<(), ConfigError> {
-    let adventure = Adventure::load(path)?;
+    let mut adventure = Adventure::load(path)?;
+    for pos in adventure.unblock_adventure_nodes() {
+        warn!(
+            "Adventure {:?}: the node at {:?} leads to another adventure, which cannot be beaten. \
+            It will not block the way.",
+            path, pos
+        );
+    }
+    load_level_metadata(&mut adventure);
+    adventure.update_reachable(&world.read_resource::<SaveGame>());
     for (pos, map_element) in &adventure.nodes {
+        let reachable = adventure.is_reachable(pos);
         match map_element {
-            MapElement::Road => load_road(pos, world),
-            MapElement::Node(node) => load_node(pos, node, world),
+            MapElement::Road => load_road(pos, reachable, world),
+            MapElement::Node(node) => load_node(pos, node, reachable, world),
         }
     }
     let initial_cursor_pos = {
         let last_known_pos = cursor_position(path, world);
-        if adventure.nodes.contains_key(&last_known_pos) {
+        if adventure.is_reachable(&last_known_pos) {
             last_known_pos
         } else {
             Pos::default()
@@ -153,6 +287,24 @@ pub fn load_adventure(path: &PathBuf, world: &mut World) -> Result<(), ConfigErr
     Ok(())
 }
 
+/// Reads the metadata of every level in the adventure from the level files.
+/// If a level cannot be read, it is shown without metadata.
+fn load_level_metadata(adventure: &mut Adventure) {
+    for map_element in adventure.nodes.values_mut() {
+        if let MapElement::Node(node) = map_element {
+            if let NodeDetails::Level(level_name) = &node.details {
+                match load_level_save(get_levels_dir().join(level_name)) {
+                    Ok(level) => node.metadata = Some(level.metadata),
+                    Err(err) => warn!(
+                        "Failed to read metadata of level {:?}: {:?}",
+                        level_name, err
+                    ),
+                }
+            }
+        }
+    }
+}
+
 fn cursor_position(path: &PathBuf, world: &mut World) -> Pos {
     world.read_resource::<UserCache>().get_initial_cursor_pos(
         path.file_name()
@@ -179,7 +331,14 @@ fn load_cursor(world: &mut World, pos: &Pos) {
         .build();
 }
 
-fn load_road(pos: &Pos, world: &mut World) {
+/// Parts of the map that the player cannot reach yet are greyed out.
+fn closed_tint() -> Tint {
+    Tint(Srgba::new(0.4, 0.4, 0.4, 0.5))
+}
+
+/// Roads that the player cannot reach yet are greyed out. They open up once the player beats the
+/// node that blocks the way.
+fn load_road(pos: &Pos, reachable: bool, world: &mut World) {
     let sprite_render_road = load_asset_from_world(&SpriteType::LevelSelect, 1, world);
     let transform = load_transform(
         pos,
@@ -187,14 +346,19 @@ fn load_road(pos: &Pos, world: &mut World) {
         &Pos::new(1, 1),
         &AssetType::Still(SpriteType::LevelSelect, 1),
     );
-    world
+    let mut builder = world
         .create_entity()
         .with(transform)
-        .with(sprite_render_road)
-        .build();
+        .with(sprite_render_road);
+    if !reachable {
+        builder = builder.with(closed_tint());
+    }
+    builder.build();
 }
 
-fn load_node(pos: &Pos, _node: &AdventureNode, world: &mut World) {
+/// Levels that the player has completed are tinted green. Nodes that the player cannot reach yet
+/// are greyed out.
+fn load_node(pos: &Pos, node: &AdventureNode, reachable: bool, world: &mut World) {
     let sprite_render_node = load_asset_from_world(&SpriteType::LevelSelect, 0, world);
     let transform = load_transform(
         pos,
@@ -202,9 +366,64 @@ fn load_node(pos: &Pos, _node: &AdventureNode, world: &mut World) {
         &Pos::new(1, 1),
         &AssetType::Still(SpriteType::LevelSelect, 0),
     );
-    world
+    let completed = match &node.details {
+        NodeDetails::Level(level_name) => {
+            world.read_resource::<SaveGame>().is_completed(level_name)
+        }
+        NodeDetails::Adventure(_) => false,
+    };
+    let mut builder = world
         .create_entity()
         .with(transform)
-        .with(sprite_render_node)
-        .build();
+        .with(sprite_render_node);
+    if !reachable {
+        builder = builder.with(closed_tint());
+    } else if completed {
+        builder = builder.with(Tint(Srgba::new(0.3, 0.9, 0.3, 1.)));
+    }
+    builder.build();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::LevelProgress;
+
+    fn node(details: NodeDetails, blocking: bool) -> MapElement {
+        MapElement::Node(AdventureNode {
+            name: String::new(),
+            details,
+            blocking,
+            unlock: None,
+            metadata: None,
+        })
+    }
+
+    #[test]
+    fn blocking_adventure_nodes_do_not_seal_the_map() {
+        let mut adventure = Adventure::default();
+        let nodes = vec![
+            node(NodeDetails::Adventure("more.ron".to_string()), true),
+            MapElement::Road,
+            node(NodeDetails::Level("a.ron".to_string()), true),
+            MapElement::Road,
+            node(NodeDetails::Level("b.ron".to_string()), false),
+        ];
+        for (x, map_element) in nodes.into_iter().enumerate() {
+            adventure.nodes.insert(Pos::new(x as i32, 0), map_element);
+        }
+        assert_eq!(adventure.unblock_adventure_nodes(), vec![Pos::new(0, 0)]);
+        assert!(adventure.unblock_adventure_nodes().is_empty());
+
+        let mut save_game = SaveGame::default();
+        adventure.update_reachable(&save_game);
+        assert!(adventure.is_reachable(&Pos::new(2, 0)));
+        // Blocking level nodes still block the way until they are beaten.
+        assert!(!adventure.is_reachable(&Pos::new(3, 0)));
+        save_game
+            .levels
+            .insert("a.ron".to_string(), LevelProgress::default());
+        adventure.update_reachable(&save_game);
+        assert!(adventure.is_reachable(&Pos::new(4, 0)));
+    }
 }
@@ -24,6 +24,9 @@ pub struct DebugSettings {
     pub skip_straight_to_editor: bool,
     /// Whether or not to display debug frames indicating the player's discrete position.
     pub display_debug_frames: bool,
+    /// If true, the player's input is recorded every time they play a level. The recording is
+    /// saved to .userdata/replays/ when they leave or restart the level.
+    pub record_replays: bool,
 }
 
 impl DebugSettings {
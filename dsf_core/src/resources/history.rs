@@ -1,29 +1,47 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+
+use amethyst::core::math::Vector2;
+
 use crate::components::*;
+use crate::resources::ToolType;
 
 /// Holds the full history of the current game. Used to rewind games to an earlier point.
 ///
-/// NB: Rewinding is currently considered a debug-only feature. It isn't currently ready for use in
-/// real game play. In the future, it will either be made an official feature or removed altogether.
+/// Every Frame is a complete snapshot of the parts of the game that can change during play. Tiles
+/// are never added during play, only removed, so the History remembers the tiles the level started
+/// out with. This allows tiles that were removed later on to be rebuilt when rewinding.
 #[derive(Debug)]
 pub struct History {
     /// If this is true, then a new Frame should be created this tick, even if nothing changed.
-    /// This is used at the start of the game to create the initial Frame, and also after rewinding,
-    /// to record the state of the game at that point.
+    /// This is used after rewinding, to record the state of the game at that point.
     pub force_key_frame: bool,
     /// A stack of Frames. Each frame records some change in game state.
     frame_stack: Vec<Frame>,
+    /// All tiles in the level as it was loaded, by their anchor position.
+    level_tiles: HashMap<Pos, String>,
+    /// The anchor positions of the level tiles that have been removed during play so far.
+    /// These are the broken blocks, collected keys and picked up tools.
+    removed_tiles: HashSet<Pos>,
 }
 
 impl Default for History {
     fn default() -> History {
+        History::new(HashMap::new())
+    }
+}
+
+impl History {
+    /// Creates an empty History for a level with the given tiles.
+    pub fn new(level_tiles: HashMap<Pos, String>) -> Self {
         History {
             force_key_frame: true,
             frame_stack: vec![],
+            level_tiles,
+            removed_tiles: HashSet::new(),
         }
     }
-}
 
-impl History {
     pub fn push_frame(&mut self, frame: Frame) {
         self.frame_stack.push(frame);
     }
@@ -31,17 +49,141 @@ impl History {
     pub fn pop_frame(&mut self) -> Option<Frame> {
         self.frame_stack.pop()
     }
+
+    /// How many moves the player has made so far. Moves that were undone or rewound are not
+    /// counted.
+    pub fn nr_moves(&self) -> u32 {
+        self.frame_stack
+            .iter()
+            .skip(1)
+            .filter(|frame| frame.checkpoint)
+            .count() as u32
+    }
+
+    /// All tiles in the level as it was loaded, by their anchor position.
+    pub fn level_tiles(&self) -> &HashMap<Pos, String> {
+        &self.level_tiles
+    }
+
+    /// Remembers that the level tile anchored at the given position was removed during play.
+    pub fn record_removed_tile(&mut self, pos: Pos) {
+        self.removed_tiles.insert(pos);
+    }
+
+    /// Returns true if the game has changed since the last Frame was pushed, or if there is no
+    /// Frame yet. Changes that happen in between two grid positions, such as the player moving
+    /// a fraction of a tile, are not counted.
+    pub fn has_changed(
+        &self,
+        steering: &Steering,
+        equipped: Option<ToolType>,
+        keys: &HashSet<Pos>,
+    ) -> bool {
+        self.frame_stack
+            .last()
+            .map(|frame| {
+                frame.steering.pos != steering.pos
+                    || frame.steering.destination != steering.destination
+                    || mem::discriminant(&frame.steering.mode) != mem::discriminant(&steering.mode)
+                    || frame.equipped != equipped
+                    || &frame.keys != keys
+                    || frame.removed_tiles != self.removed_tiles
+            })
+            .unwrap_or(true)
+    }
+
+    /// Creates a Frame of the current state of the game.
+    ///
+    /// The Frame is a checkpoint if it is the first Frame, if the player has just completed a move
+    /// or if they picked up a key or tool or used a tool since the last Frame.
+    pub fn create_frame(
+        &self,
+        translation: Vector2<f32>,
+        steering: &Steering,
+        equipped: Option<ToolType>,
+        keys: &HashSet<Pos>,
+    ) -> Frame {
+        let checkpoint = self
+            .frame_stack
+            .last()
+            .map(|frame| {
+                completed_move(steering)
+                    || frame.equipped != equipped
+                    || &frame.keys != keys
+                    || frame.removed_tiles != self.removed_tiles
+            })
+            .unwrap_or(true);
+        Frame {
+            translation,
+            steering: steering.clone(),
+            equipped,
+            keys: keys.clone(),
+            removed_tiles: self.removed_tiles.clone(),
+            checkpoint,
+        }
+    }
+
+    /// Undoes the last move. Drops all Frames after the previous checkpoint and returns that
+    /// checkpoint. The checkpoint itself stays on the stack, because it describes the game after
+    /// undoing.
+    ///
+    /// If the player is in the middle of a move, that move is undone. Returns None if there is
+    /// nothing to undo.
+    pub fn undo(&mut self) -> Option<Frame> {
+        if self.frame_stack.len() < 2 {
+            return None;
+        }
+        self.frame_stack.pop();
+        while self.frame_stack.len() > 1
+            && !self
+                .frame_stack
+                .last()
+                .map(|frame| frame.checkpoint)
+                .unwrap_or(true)
+        {
+            self.frame_stack.pop();
+        }
+        self.frame_stack.last().cloned()
+    }
+
+    /// Rewinds the removed tiles to those of the given frame. Returns the tiles that were removed
+    /// after that frame, which must now be rebuilt, together with their tile definition keys.
+    pub fn restore_tiles(&mut self, frame: &Frame) -> Vec<(Pos, String)> {
+        let restored = self
+            .removed_tiles
+            .difference(&frame.removed_tiles)
+            .filter_map(|pos| {
+                self.level_tiles
+                    .get(pos)
+                    .map(|tile_def_key| (*pos, tile_def_key.clone()))
+            })
+            .collect();
+        self.removed_tiles = frame.removed_tiles.clone();
+        restored
+    }
 }
 
-#[derive(Debug)]
+/// A snapshot of everything in the game that can change during play.
+#[derive(Debug, Clone)]
 pub struct Frame {
-    pub player_position: Pos,
+    /// The exact translation of the player. Needed to resume jumps and falls from the right height.
+    pub translation: Vector2<f32>,
+    /// The full steering of the player, including the steering mode.
+    pub steering: Steering,
+    /// The tool the player had equipped.
+    pub equipped: Option<ToolType>,
+    /// The keys that were left in the level.
+    pub keys: HashSet<Pos>,
+    /// The anchor positions of the level tiles that had been removed.
+    pub removed_tiles: HashSet<Pos>,
+    /// Whether undoing a move may return to this Frame. Rewinding returns to every Frame.
+    pub checkpoint: bool,
 }
 
-impl Frame {
-    pub fn new(player_position: Pos) -> Self {
-        Frame { player_position }
-    }
+/// Returns true if the player has arrived at their destination and stands or hangs still there.
+/// This is the case after every completed step, climb and landing.
+fn completed_move(steering: &Steering) -> bool {
+    !steering.is_mid_air() && steering.pos == steering.destination
 }
 
 /// Used to toggle systems on and off. Some systems can only run if the game is running normally.
@@ -63,6 +205,8 @@ impl Default for CurrentState {
 pub struct Rewind {
     /// The time in seconds until a new Frame can be popped off the History.
     pub cooldown: f32,
+    /// Set to true when the player wants to undo their last move. The move is undone next tick.
+    pub undo_requested: bool,
 }
 
 impl Rewind {
@@ -70,3 +214,105 @@ impl Rewind {
         self.cooldown.is_sign_negative()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame with the player standing at the given x, that is or is not a checkpoint.
+    fn frame(x: i32, checkpoint: bool) -> Frame {
+        Frame {
+            translation: Vector2::new(x as f32 + 1., 1.),
+            steering: Steering::new(Pos::new(x, 0), Pos::new(2, 2)),
+            equipped: None,
+            keys: HashSet::new(),
+            removed_tiles: HashSet::new(),
+            checkpoint,
+        }
+    }
+
+    fn history(frames: Vec<Frame>) -> History {
+        let mut history = History::default();
+        frames
+            .into_iter()
+            .for_each(|frame| history.push_frame(frame));
+        history
+    }
+
+    #[test]
+    fn undo_returns_to_the_previous_checkpoint() {
+        let mut history = history(vec![
+            frame(0, true),
+            frame(1, true),
+            frame(1, false),
+            frame(2, true),
+        ]);
+        assert_eq!(history.nr_moves(), 2);
+        let undone = history.undo().expect("There is a move to undo.");
+        assert_eq!(undone.steering.pos, Pos::new(1, 0));
+        assert_eq!(history.nr_moves(), 1);
+        let undone = history.undo().expect("There is a move to undo.");
+        assert_eq!(undone.steering.pos, Pos::new(0, 0));
+        assert_eq!(history.nr_moves(), 0);
+    }
+
+    #[test]
+    fn undo_in_the_middle_of_a_move_undoes_that_move() {
+        let mut history = history(vec![frame(0, true), frame(1, true), frame(1, false)]);
+        let undone = history.undo().expect("There is a move to undo.");
+        assert_eq!(undone.steering.pos, Pos::new(1, 0));
+        assert!(undone.checkpoint);
+    }
+
+    #[test]
+    fn the_first_frame_cannot_be_undone() {
+        let mut history = history(vec![frame(0, true)]);
+        assert!(history.undo().is_none());
+        assert!(History::default().undo().is_none());
+    }
+
+    #[test]
+    fn first_frame_is_always_a_checkpoint() {
+        let history = History::default();
+        let steering = Steering::new(Pos::new(0, 0), Pos::new(2, 2));
+        assert!(history.has_changed(&steering, None, &HashSet::new()));
+        let frame = history.create_frame(Vector2::new(1., 1.), &steering, None, &HashSet::new());
+        assert!(frame.checkpoint);
+    }
+
+    #[test]
+    fn picking_up_a_tool_is_a_checkpoint_even_mid_move() {
+        let mut history = history(vec![frame(0, true)]);
+        let mut steering = Steering::new(Pos::new(0, 0), Pos::new(2, 2));
+        steering.destination = Pos::new(1, 0);
+        let tool = Some(ToolType::BreakBlocksBelow(2));
+        assert!(history.has_changed(&steering, tool, &HashSet::new()));
+        let frame = history.create_frame(Vector2::new(1.5, 1.), &steering, tool, &HashSet::new());
+        assert!(frame.checkpoint);
+        let frame = history.create_frame(Vector2::new(1.5, 1.), &steering, None, &HashSet::new());
+        assert!(!frame.checkpoint);
+        history.push_frame(frame);
+        assert_eq!(history.nr_moves(), 0);
+    }
+
+    #[test]
+    fn restoring_tiles_rebuilds_the_tiles_removed_since_the_frame() {
+        let mut level_tiles = HashMap::new();
+        level_tiles.insert(Pos::new(3, 0), "Key".to_string());
+        level_tiles.insert(Pos::new(5, -1), "Block1".to_string());
+        let mut history = History::new(level_tiles);
+        let before = frame(0, true);
+        history.record_removed_tile(Pos::new(3, 0));
+        history.record_removed_tile(Pos::new(5, -1));
+        let mut restored = history.restore_tiles(&before);
+        restored.sort();
+        assert_eq!(
+            restored,
+            vec![
+                (Pos::new(3, 0), "Key".to_string()),
+                (Pos::new(5, -1), "Block1".to_string())
+            ]
+        );
+        assert!(history.restore_tiles(&before).is_empty());
+    }
+}
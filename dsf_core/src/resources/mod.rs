@@ -5,6 +5,8 @@ mod event_readers;
 mod gui;
 mod history;
 mod music;
+mod replay;
+mod save_game;
 mod signal_edge_detector;
 mod tile_defs;
 mod tilemap;
@@ -19,6 +21,8 @@ pub use self::event_readers::*;
 pub use self::gui::*;
 pub use self::history::*;
 pub use self::music::*;
+pub use self::replay::*;
+pub use self::save_game::*;
 pub use self::signal_edge_detector::*;
 pub use self::tile_defs::*;
 pub use self::tilemap::*;
@@ -0,0 +1,206 @@
+use crate::sim::PlayerInput;
+use amethyst::config::ConfigError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A recording of the player's input during a play session. The game is deterministic, so feeding
+/// the same input to the same level always plays out exactly the same way. This makes replays
+/// useful both to reproduce bugs and to show a solution to a level.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct Replay {
+    /// The level file that was played.
+    pub level_file: PathBuf,
+    /// Hash of the contents of the level file at the time of recording. If the level has changed
+    /// since, the replay will most likely play out differently.
+    pub level_hash: String,
+    /// The player's input for every tick, in order. Consecutive ticks with the same input are
+    /// stored as a single run.
+    pub inputs: Vec<InputRun>,
+}
+
+/// The same input, held for a number of consecutive ticks.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct InputRun {
+    pub ticks: u32,
+    pub input: PlayerInput,
+}
+
+impl Replay {
+    pub fn new(level_file: PathBuf, level_hash: String) -> Self {
+        Replay {
+            level_file,
+            level_hash,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Appends the input of the next tick to the recording.
+    pub fn push_input(&mut self, input: PlayerInput) {
+        match self.inputs.last_mut() {
+            Some(run) if run.input == input => run.ticks += 1,
+            _ => self.inputs.push(InputRun { ticks: 1, input }),
+        }
+    }
+
+    /// The total number of ticks in the recording.
+    pub fn nr_ticks(&self) -> u64 {
+        self.inputs.iter().map(|run| run.ticks as u64).sum()
+    }
+
+    /// The input of every tick in the recording, in order.
+    pub fn iter_inputs(&self) -> impl Iterator<Item = PlayerInput> + '_ {
+        self.inputs
+            .iter()
+            .flat_map(|run| (0..run.ticks).map(move |_| run.input))
+    }
+}
+
+/// Whether the PlayState records the player's input, plays back an earlier recording, or does
+/// neither.
+#[derive(Debug)]
+pub enum ReplayMode {
+    Off,
+    Recording(Replay),
+    PlayingBack {
+        replay: Replay,
+        /// Index of the run that is currently being played back.
+        run: usize,
+        /// How many ticks of that run have been played back already.
+        tick_in_run: u32,
+    },
+}
+
+impl Default for ReplayMode {
+    fn default() -> Self {
+        ReplayMode::Off
+    }
+}
+
+impl ReplayMode {
+    pub fn play_back(replay: Replay) -> Self {
+        ReplayMode::PlayingBack {
+            replay,
+            run: 0,
+            tick_in_run: 0,
+        }
+    }
+
+    /// Returns true if the player's input is either being recorded or played back. Rewinding
+    /// and undoing are not recorded, so they are disabled in both cases.
+    pub fn is_active(&self) -> bool {
+        !matches!(self, ReplayMode::Off)
+    }
+
+    /// Returns the input to use for the current tick.
+    ///
+    /// While recording, the live input is recorded and returned. While playing back, the live
+    /// input is ignored and the recorded input is returned instead. Once the recording runs out,
+    /// the player lets go of all controls.
+    pub fn next_input(&mut self, live_input: PlayerInput) -> PlayerInput {
+        match self {
+            ReplayMode::Off => live_input,
+            ReplayMode::Recording(replay) => {
+                replay.push_input(live_input);
+                live_input
+            }
+            ReplayMode::PlayingBack {
+                replay,
+                run,
+                tick_in_run,
+            } => {
+                while replay
+                    .inputs
+                    .get(*run)
+                    .map(|input_run| *tick_in_run >= input_run.ticks)
+                    .unwrap_or(false)
+                {
+                    *run += 1;
+                    *tick_in_run = 0;
+                }
+                *tick_in_run += 1;
+                replay
+                    .inputs
+                    .get(*run)
+                    .map(|input_run| input_run.input)
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// Calculates a hash of the contents of the given level file. Uses 64-bit FNV-1a, which unlike
+/// the standard library's hasher is guaranteed to give the same result on every platform and in
+/// every version of the game.
+pub fn hash_level_file<P: AsRef<Path>>(level_file: P) -> Result<String, ConfigError> {
+    let contents = fs::read(level_file).map_err(ConfigError::File)?;
+    let hash = contents
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+    Ok(format!("{:016x}", hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: PlayerInput = PlayerInput {
+        move_x: -1.,
+        move_y: 0.,
+        jump: false,
+    };
+    const JUMP: PlayerInput = PlayerInput {
+        move_x: 0.,
+        move_y: 0.,
+        jump: true,
+    };
+
+    #[test]
+    fn recording_merges_ticks_with_the_same_input() {
+        let mut mode = ReplayMode::Recording(Replay::default());
+        let inputs = [LEFT, LEFT, JUMP, LEFT];
+        for input in inputs.iter() {
+            assert_eq!(mode.next_input(*input), *input);
+        }
+        if let ReplayMode::Recording(replay) = mode {
+            let runs = replay
+                .inputs
+                .iter()
+                .map(|run| (run.ticks, run.input))
+                .collect::<Vec<_>>();
+            assert_eq!(runs, vec![(2, LEFT), (1, JUMP), (1, LEFT)]);
+            assert_eq!(replay.nr_ticks(), 4);
+            assert_eq!(replay.iter_inputs().collect::<Vec<_>>(), inputs.to_vec());
+        } else {
+            panic!("Recording stopped.");
+        }
+    }
+
+    #[test]
+    fn playing_back_ignores_live_input_and_stops_at_the_end() {
+        let mut replay = Replay::default();
+        replay.push_input(JUMP);
+        replay.push_input(LEFT);
+        replay.push_input(LEFT);
+        let mut mode = ReplayMode::play_back(replay);
+        assert!(mode.is_active());
+        assert_eq!(mode.next_input(LEFT), JUMP);
+        assert_eq!(mode.next_input(JUMP), LEFT);
+        assert_eq!(mode.next_input(JUMP), LEFT);
+        assert_eq!(mode.next_input(JUMP), PlayerInput::default());
+        assert_eq!(mode.next_input(JUMP), PlayerInput::default());
+    }
+
+    #[test]
+    fn off_passes_live_input_through() {
+        let mut mode = ReplayMode::default();
+        assert!(!mode.is_active());
+        assert_eq!(mode.next_input(JUMP), JUMP);
+    }
+}
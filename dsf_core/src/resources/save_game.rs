@@ -0,0 +1,63 @@
+use crate::utility::files::get_save_game_file;
+use amethyst::config::Config;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The player's progress through the game: which levels they completed and their best results.
+#[derive(Debug, Deserialize, Serialize, Default)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct SaveGame {
+    /// Maps the level file name (e.g. "confined.ron") to the player's results in that level.
+    /// Levels that were never completed are not in here.
+    pub levels: HashMap<String, LevelProgress>,
+}
+
+/// The player's results in a single level.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct LevelProgress {
+    /// How many times the player completed the level.
+    pub times_completed: u32,
+    /// The fastest the player ever completed the level, in seconds.
+    pub best_time: f32,
+    /// The fewest moves the player ever needed to complete the level.
+    pub best_moves: u32,
+}
+
+impl SaveGame {
+    pub fn is_completed(&self, level_name: &str) -> bool {
+        self.levels.contains_key(level_name)
+    }
+
+    pub fn progress(&self, level_name: &str) -> Option<&LevelProgress> {
+        self.levels.get(level_name)
+    }
+
+    /// Records that the player completed the given level, in the given number of seconds and
+    /// moves. Keeps the best time and the best move count separately, they may come from
+    /// different attempts. Call `save` to write the result to disk.
+    pub fn record_completion(&mut self, level_name: String, seconds: f32, moves: u32) {
+        let progress = self
+            .levels
+            .entry(level_name)
+            .and_modify(|progress| {
+                progress.best_time = progress.best_time.min(seconds);
+                progress.best_moves = progress.best_moves.min(moves);
+            })
+            .or_insert(LevelProgress {
+                times_completed: 0,
+                best_time: seconds,
+                best_moves: moves,
+            });
+        progress.times_completed += 1;
+    }
+
+    /// Writes the save game to disk.
+    pub fn save(&self) {
+        self.write(get_save_game_file()).unwrap_or_else(|err| {
+            error!("Failed to save {:?} because error: {:?}", self, err);
+        });
+    }
+}
@@ -74,6 +74,8 @@ pub struct TileDefinition {
     pub archetype: Option<Archetype>,
     /// If and under what circumstances this block can be destroyed.
     pub sturdiness: Sturdiness,
+    /// The group this tile is listed under in the editor's palette. Has no effect on gameplay.
+    pub category: TileCategory,
 }
 
 impl TileDefinition {
@@ -91,6 +93,7 @@ impl TileDefinition {
             preview_asset: None,
             archetype: None,
             sturdiness: Sturdiness::Invulnerable,
+            category: TileCategory::Other,
         }
     }
 
@@ -213,7 +216,7 @@ impl Default for Sturdiness {
     }
 }
 
-#[derive(Debug, Copy, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Copy, Clone, Deserialize, Serialize, PartialEq, Eq, Hash)]
 #[serde(deny_unknown_fields)]
 pub enum ToolType {
     /// This tool will break the blocks that the player is facing, n layers deep.
@@ -246,3 +249,25 @@ pub struct CollisionDefinition {
     /// When standing underneath a two-high ledge of these tiles, the player cannot jump.
     pub collides_bottom: bool,
 }
+
+/// The group a tile belongs to in the editor's palette.
+/// Tiles are listed in the order of these variants, then alphabetically.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TileCategory {
+    /// Blocks that make up the walls, floors and platforms of a level.
+    Terrain,
+    /// Tiles that can be climbed, such as ladders.
+    Climbing,
+    /// The player, the exit door, keys and anything else that is needed to win.
+    Objectives,
+    /// Tools that the player can pick up.
+    Tools,
+    /// Anything that does not fit in another category.
+    Other,
+}
+
+impl Default for TileCategory {
+    fn default() -> Self {
+        TileCategory::Other
+    }
+}
@@ -30,7 +30,7 @@ impl TileMap {
             })
             .filter(|(_, _, tile_def)| {
                 // Make sure we only add relevant stuff to the tile map.
-                !apply_filter || tile_def.climbable || tile_def.collision.is_some() || tile_def.is_breakable()
+                !apply_filter || is_relevant_for_play(tile_def)
             })
             .for_each(|(pos, key, tile_def)| {
                 let dimens = tile_def.dimens;
@@ -117,6 +117,16 @@ impl TileMap {
         }
     }
 
+    /// Puts a tile that was removed during play back in place. Used when rewinding.
+    /// Tiles that are not kept in the TileMap during play are ignored.
+    pub fn restore_tile(&mut self, pos: Pos, tile_def_key: &str) {
+        let tile_def = self.tile_defs.get(tile_def_key);
+        if is_relevant_for_play(tile_def) {
+            let dimens = tile_def.dimens;
+            self.put_tile(pos, tile_def_key.to_string(), &dimens);
+        }
+    }
+
     pub fn put_tile(&mut self, pos: Pos, tile_def_key: String, dimensions: &Pos) {
         self.tiles.insert(pos, Tile::TileDefKey(tile_def_key));
         (0..dimensions.x).for_each(|x| {
@@ -129,6 +139,11 @@ impl TileMap {
     }
 }
 
+/// Only climbable, collidable and destructable tiles are kept in the TileMap during play.
+fn is_relevant_for_play(tile_def: &TileDefinition) -> bool {
+    tile_def.climbable || tile_def.collision.is_some() || tile_def.is_breakable()
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Tile {
     /// A dummy tile, points towards its anchor point, where the real tile is stored.
@@ -5,7 +5,7 @@ use crate::components::*;
 /// Maintains some information related to winning the level.
 /// In any given level, the player must collect all keys. Once all keys are collected, the exit door
 /// opens. When the player then reaches the door, they complete the level.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct WinCondition {
     /// The set of positions of keys that are left in the level. If this collection is empty, then
     /// the player has collected all keys and is free to finish the level by reaching the exit door.
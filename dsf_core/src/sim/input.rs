@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// The state of the player's controls during a single tick.
+/// During play this is read from the InputHandler; in the headless simulation it is supplied
+/// directly by the caller.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerInput {
+    /// Horizontal movement axis, between -1.0 (left) and 1.0 (right).
+    pub move_x: f32,
+    /// Vertical movement axis, between -1.0 (down) and 1.0 (up).
+    pub move_y: f32,
+    /// Whether the jump button is held down.
+    pub jump: bool,
+}
@@ -0,0 +1,9 @@
+//! A headless version of the game's core rules. Runs the same movement, key, tool and win logic
+//! as the PlayState, but without a window, sprites, audio or an ECS world.
+//! Useful for testing gameplay and for running solvers on machines without a GPU.
+
+mod input;
+mod simulation;
+
+pub use self::input::*;
+pub use self::simulation::*;
@@ -0,0 +1,454 @@
+use amethyst::core::math::Vector2;
+
+use crate::components::*;
+use crate::levels::LevelSave;
+use crate::resources::*;
+use crate::sim::PlayerInput;
+use crate::systems::*;
+
+/// The number of seconds that pass during a single tick of the simulation.
+/// This matches the fixed timestep that the game runs its systems at.
+pub const TICK_SECONDS: f32 = 1. / 60.;
+
+/// A headless, deterministic simulation of a single level.
+///
+/// Every call to `step` advances the simulation by one fixed tick, applying the same rules as the
+/// systems in the PlayState, in the same order: player input, steering, movement, velocity, key
+/// collection, tool pickup, tool usage, level wrapping and finally the win check.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    player: Player,
+    steering: Steering,
+    intent: SteeringIntent,
+    velocity: Velocity,
+    /// The player's actual position in the world. Plays the role of the Transform.
+    translation: Vector2<f32>,
+    /// Keys that have not been collected yet. Position of the key, with the center of its collider.
+    keys: Vec<(Pos, Vector2<f32>)>,
+    /// Tools that have not been picked up yet. Position of the tool, its type and the center of its
+    /// collider.
+    tools: Vec<(Pos, ToolType, Vector2<f32>)>,
+    /// The center of each exit door.
+    doors: Vec<Vector2<f32>>,
+    win_condition: WinCondition,
+    tile_map: TileMap,
+    /// Positions of all blocks that were broken with a tool, in the order they were broken.
+    broken_blocks: Vec<Pos>,
+    config: MovementConfig,
+    /// Sound effects that were emitted since the last time they were drained.
+    sounds: Vec<SoundType>,
+    ticks: u64,
+}
+
+impl Simulation {
+    /// Sets up a simulation for the given level.
+    /// Returns None if the level does not contain a player.
+    pub fn new(
+        level: LevelSave,
+        tile_defs: TileDefinitions,
+        config: MovementConfig,
+    ) -> Option<Self> {
+        // Sort the tiles, so the simulation does not depend on the HashMap's iteration order.
+        let mut tiles = level.tiles.iter().collect::<Vec<_>>();
+        tiles.sort_by_key(|(pos, _)| **pos);
+        let mut player_steering = None;
+        let mut keys = Vec::new();
+        let mut tools = Vec::new();
+        let mut doors = Vec::new();
+        let mut win_condition = WinCondition::default();
+        for (pos, tile_def_key) in tiles {
+            let tile_def = tile_defs.get(tile_def_key);
+            let center = Vector2::new(
+                pos.x as f32 + tile_def.dimens.x as f32 * 0.5,
+                pos.y as f32 + tile_def.dimens.y as f32 * 0.5,
+            );
+            match tile_def.archetype {
+                Some(Archetype::Player) => {
+                    if player_steering.is_none() {
+                        player_steering = Some((Steering::new(*pos, tile_def.dimens), center));
+                    } else {
+                        error!(
+                            "Level contains multiple players, ignoring player at {:?}",
+                            pos
+                        );
+                    }
+                }
+                Some(Archetype::Key) => {
+                    win_condition.add_key(pos);
+                    keys.push((*pos, center));
+                }
+                Some(Archetype::Tool(tool_type)) => tools.push((*pos, tool_type, center)),
+                Some(Archetype::Door) => doors.push(center),
+                _ => (),
+            }
+        }
+        let (steering, translation) = player_steering?;
+        Some(Simulation {
+            player: Player::default(),
+            steering,
+            intent: SteeringIntent::default(),
+            velocity: Velocity::default(),
+            translation,
+            keys,
+            tools,
+            doors,
+            win_condition,
+            tile_map: TileMap::for_play(level, tile_defs),
+            broken_blocks: Vec::new(),
+            config,
+            sounds: Vec::new(),
+            ticks: 0,
+        })
+    }
+
+    /// Advance the simulation by a single tick, with the given player input.
+    pub fn step(&mut self, input: &PlayerInput) {
+        update_player_intent(
+            &mut self.player,
+            &mut self.intent,
+            &self.steering,
+            input,
+            &self.config,
+            TICK_SECONDS,
+        );
+        steer(
+            &mut self.steering,
+            &mut self.intent,
+            &self.translation,
+            &self.tile_map,
+            TICK_SECONDS,
+            &mut self.sounds,
+        );
+        apply_steering(
+            &self.steering,
+            &mut self.translation,
+            &mut self.velocity,
+            &self.config,
+        );
+        apply_velocity(&mut self.translation, &self.velocity, TICK_SECONDS);
+        self.collect_keys();
+        self.pick_up_tool();
+        self.use_tool(input);
+        wrap_around(
+            &mut self.translation,
+            &mut self.steering,
+            &self.tile_map.world_bounds,
+        );
+        self.check_win();
+        self.ticks += 1;
+    }
+
+    /// Advance the simulation by the given number of ticks, holding the same input all along.
+    pub fn step_n(&mut self, input: &PlayerInput, ticks: usize) {
+        for _ in 0..ticks {
+            self.step(input);
+        }
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn steering(&self) -> &Steering {
+        &self.steering
+    }
+
+    /// The player's actual position, the equivalent of the player Transform's translation.
+    pub fn translation(&self) -> &Vector2<f32> {
+        &self.translation
+    }
+
+    pub fn win_condition(&self) -> &WinCondition {
+        &self.win_condition
+    }
+
+    pub fn tile_map(&self) -> &TileMap {
+        &self.tile_map
+    }
+
+    /// Positions of the tools that have not been picked up yet.
+    pub fn tools_left(&self) -> Vec<Pos> {
+        self.tools.iter().map(|(pos, _, _)| *pos).collect()
+    }
+
+    /// Positions of all blocks that were broken with a tool, in the order they were broken.
+    pub fn broken_blocks(&self) -> &[Pos] {
+        &self.broken_blocks
+    }
+
+    /// Whether the player is standing still on the grid, either on the ground or on a ladder.
+    /// When this is true, the player's next move is determined purely by their input.
+    pub fn is_at_rest(&self) -> bool {
+        let (centered_x, centered_y) = self.steering.to_centered_coords(self.steering.pos);
+        (self.steering.is_grounded() || self.steering.is_climbing())
+            && self.steering.pos == self.steering.destination
+            && (self.translation.x - centered_x).abs() < f32::EPSILON
+            && (self.translation.y - centered_y).abs() < f32::EPSILON
+    }
+
+    /// Number of ticks that have passed since the simulation started.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Whether the player has collected all keys and subsequently reached the exit door.
+    pub fn has_won(&self) -> bool {
+        self.win_condition.reached_open_door
+    }
+
+    /// Returns all sound effects emitted since the last time this was called, in order.
+    pub fn drain_sounds(&mut self) -> Vec<SoundType> {
+        self.sounds.drain(..).collect()
+    }
+
+    fn player_dimens(&self) -> Vector2<f32> {
+        Vector2::new(self.steering.dimens.x as f32, self.steering.dimens.y as f32)
+    }
+
+    /// Mirrors the KeyCollectionSystem.
+    fn collect_keys(&mut self) {
+        let dimens = self.player_dimens();
+        let translation = self.translation;
+        let collected_key = self.keys.iter().position(|(_, center)| {
+            touches(
+                &translation,
+                &dimens,
+                center,
+                &Vector2::new(KEY_WIDTH, KEY_HEIGHT),
+            )
+        });
+        if let Some(index) = collected_key {
+            let (pos, _) = self.keys.remove(index);
+            self.sounds.push(SoundType::KeyPickup);
+            self.win_condition.set_key_collected(&pos);
+        }
+    }
+
+    /// Mirrors the PickupSystem.
+    fn pick_up_tool(&mut self) {
+        if self.player.equipped.is_some() {
+            return;
+        }
+        let dimens = self.player_dimens();
+        let translation = self.translation;
+        let tool = self.tools.iter().position(|(_, _, center)| {
+            touches(
+                &translation,
+                &dimens,
+                center,
+                &Vector2::new(TOOL_WIDTH, TOOL_HEIGHT),
+            )
+        });
+        if let Some(index) = tool {
+            let (_, tool_type, _) = self.tools.remove(index);
+            self.sounds.push(SoundType::ToolPickup);
+            self.player.equipped = Some(tool_type);
+        }
+    }
+
+    /// Mirrors the UseToolSystem.
+    fn use_tool(&mut self, input: &PlayerInput) {
+        if !input.jump {
+            return;
+        }
+        if let Some(targeted_blocks) = blocks_to_break(&self.player, &self.steering, &self.tile_map)
+        {
+            self.sounds.push(SoundType::Mining);
+            self.player.equipped = None;
+            targeted_blocks.iter().for_each(|pos| {
+                if let Some(pos) = self.tile_map.remove_tile(pos) {
+                    self.broken_blocks.push(pos);
+                }
+            });
+        }
+    }
+
+    /// Mirrors the WinSystem.
+    fn check_win(&mut self) {
+        if self.win_condition.reached_open_door || !self.win_condition.all_keys_collected() {
+            return;
+        }
+        let dimens = self.player_dimens();
+        let reached_door = self.doors.iter().any(|center| {
+            touches(
+                &self.translation,
+                &dimens,
+                center,
+                &Vector2::new(DOOR_WIDTH, DOOR_HEIGHT),
+            )
+        });
+        if reached_door {
+            self.sounds.push(SoundType::Win);
+            self.win_condition.reached_open_door = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::load_tile_definitions;
+    use crate::utility::files::get_config_dir;
+    use amethyst::config::Config;
+
+    const NEUTRAL: PlayerInput = PlayerInput {
+        move_x: 0.,
+        move_y: 0.,
+        jump: false,
+    };
+    const RIGHT: PlayerInput = PlayerInput {
+        move_x: 1.,
+        move_y: 0.,
+        jump: false,
+    };
+    const JUMP_RIGHT: PlayerInput = PlayerInput {
+        move_x: 1.,
+        move_y: 0.,
+        jump: true,
+    };
+
+    /// A level with a floor at y = -1, running from x = -10 up to and including x = 9, the player
+    /// at the given position and the given other tiles.
+    fn level(player: Pos, tiles: &[(i32, i32, &str)]) -> LevelSave {
+        let mut level = LevelSave {
+            world_bounds: WorldBounds::new(-10, -5, 20, 15),
+            ..LevelSave::default()
+        };
+        for x in -10..10 {
+            level.tiles.insert(Pos::new(x, -1), "Block1".to_string());
+        }
+        level.tiles.insert(player, "Player".to_string());
+        for (x, y, key) in tiles {
+            level.tiles.insert(Pos::new(*x, *y), key.to_string());
+        }
+        level
+    }
+
+    fn simulation(level: LevelSave) -> Simulation {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let config = MovementConfig::load(get_config_dir().join("movement.ron"))
+            .expect("Failed to load movement config.");
+        Simulation::new(level, tile_defs, config).expect("Level has no player.")
+    }
+
+    /// Lets go of all controls until the player stands still on the grid.
+    fn settle(sim: &mut Simulation) {
+        for _ in 0..120 {
+            if sim.is_at_rest() {
+                return;
+            }
+            sim.step(&NEUTRAL);
+        }
+        panic!("Player did not come to rest at {:?}.", sim.steering());
+    }
+
+    #[test]
+    fn level_without_player_cannot_be_simulated() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        assert!(
+            Simulation::new(LevelSave::default(), tile_defs, MovementConfig::default()).is_none()
+        );
+    }
+
+    #[test]
+    fn player_starts_at_rest() {
+        let sim = simulation(level(Pos::new(0, 0), &[]));
+        assert_eq!(sim.steering().pos, Pos::new(0, 0));
+        assert!(sim.is_at_rest());
+        assert!(!sim.has_won());
+        assert_eq!(sim.ticks(), 0);
+    }
+
+    #[test]
+    fn tapping_right_walks_a_single_tile() {
+        let mut sim = simulation(level(Pos::new(0, 0), &[]));
+        sim.step(&RIGHT);
+        settle(&mut sim);
+        assert_eq!(sim.steering().pos, Pos::new(1, 0));
+        assert!(sim.steering().is_grounded());
+        assert_eq!(sim.drain_sounds(), vec![SoundType::Step]);
+    }
+
+    #[test]
+    fn player_falls_down_onto_the_floor() {
+        let mut sim = simulation(level(Pos::new(0, 4), &[]));
+        sim.step(&NEUTRAL);
+        assert!(sim.steering().is_falling());
+        settle(&mut sim);
+        assert_eq!(sim.steering().pos, Pos::new(0, 0));
+        assert!(sim.steering().is_grounded());
+    }
+
+    #[test]
+    fn jumping_lands_back_on_the_floor() {
+        let mut sim = simulation(level(Pos::new(0, 0), &[]));
+        sim.step(&JUMP_RIGHT);
+        assert!(sim.steering().is_jumping());
+        settle(&mut sim);
+        assert_eq!(sim.steering().pos.y, 0);
+        assert!(sim.steering().pos.x > 0);
+        assert_eq!(sim.drain_sounds().first(), Some(&SoundType::Jump));
+    }
+
+    #[test]
+    fn door_only_opens_once_all_keys_are_collected() {
+        let mut sim = simulation(level(Pos::new(0, 0), &[(-6, 0, "Key"), (4, 0, "Door")]));
+        sim.step_n(&RIGHT, 30);
+        assert!(!sim.has_won());
+        assert!(!sim.win_condition().all_keys_collected());
+    }
+
+    #[test]
+    fn collecting_the_key_and_reaching_the_door_wins() {
+        let mut sim = simulation(level(Pos::new(0, 0), &[(2, 0, "Key"), (5, 0, "Door")]));
+        sim.step_n(&RIGHT, 60);
+        assert!(sim.has_won());
+        let sounds = sim.drain_sounds();
+        let key_pickup = sounds
+            .iter()
+            .position(|sound| *sound == SoundType::KeyPickup);
+        let win = sounds.iter().position(|sound| *sound == SoundType::Win);
+        assert!(key_pickup.is_some());
+        assert!(key_pickup < win);
+    }
+
+    #[test]
+    fn pickaxe_breaks_the_blocks_below_and_in_front() {
+        let mut sim = simulation(level(Pos::new(0, 0), &[(2, 0, "ToolPickaxe")]));
+        sim.step_n(&RIGHT, 6);
+        settle(&mut sim);
+        assert_eq!(sim.player().equipped, Some(ToolType::BreakBlocksBelow(2)));
+        assert!(sim.tools_left().is_empty());
+        sim.step(&PlayerInput {
+            jump: true,
+            ..NEUTRAL
+        });
+        assert_eq!(sim.player().equipped, None);
+        let mut broken = sim.broken_blocks().to_vec();
+        broken.sort();
+        // The player faces right, so the pickaxe digs in front of their feet.
+        let pos = sim.steering().pos;
+        assert_eq!(
+            broken,
+            vec![Pos::new(pos.x + 1, -1), Pos::new(pos.x + 2, -1)]
+        );
+    }
+
+    #[test]
+    fn same_inputs_give_the_same_result() {
+        let inputs = [(RIGHT, 10), (JUMP_RIGHT, 1), (NEUTRAL, 40), (RIGHT, 5)];
+        let run = || {
+            let mut sim = simulation(level(Pos::new(0, 0), &[(6, 0, "Key")]));
+            for (input, ticks) in inputs.iter() {
+                sim.step_n(input, *ticks);
+            }
+            sim
+        };
+        let first = run();
+        let second = run();
+        assert_eq!(first.translation(), second.translation());
+        assert_eq!(first.steering().pos, second.steering().pos);
+        assert_eq!(first.ticks(), second.ticks());
+        assert_eq!(first.win_condition().keys, second.win_condition().keys);
+    }
+}
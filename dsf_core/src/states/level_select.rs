@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use amethyst::prelude::WorldExt;
+use amethyst::prelude::{Config, WorldExt};
 
 use amethyst::StateEvent;
 use amethyst::{
@@ -13,7 +13,7 @@ use amethyst::{GameData, SimpleState, SimpleTrans};
 use crate::entities::*;
 
 use crate::resources::*;
-use crate::states::{window_event_handler, PlayState};
+use crate::states::{replay_file, window_event_handler, PlayState};
 use crate::systems;
 use crate::utility::files::{get_adventures_dir, get_levels_dir};
 use amethyst::core::ecs::{Dispatcher, DispatcherBuilder, Read, Write};
@@ -61,7 +61,8 @@ impl<'a, 'b> LevelSelectState {
                         details: NodeDetails::Level(level_name),
                         ..
                     })) => {
-                        let play_state = PlayState::new(get_levels_dir().join(level_name));
+                        let play_state =
+                            PlayState::from_adventure(get_levels_dir().join(level_name));
                         Trans::Push(Box::new(play_state))
                     }
                     _ => Trans::None,
@@ -70,6 +71,40 @@ impl<'a, 'b> LevelSelectState {
         )
     }
 
+    /// Call this when the user wants to watch the most recent replay of the selected level.
+    /// Does nothing if the user selected something other than a level, or if that level has not
+    /// been recorded yet.
+    fn watch_replay(world: &mut World) -> SimpleTrans {
+        world.exec(
+            |(adventure, pos_on_map): (Read<Adventure>, Read<PositionOnMap>)| {
+                let selected_node = adventure.nodes.get(&pos_on_map.pos);
+                match selected_node {
+                    Some(MapElement::Node(AdventureNode {
+                        details: NodeDetails::Level(level_name),
+                        ..
+                    })) => {
+                        let level_file = get_levels_dir().join(level_name);
+                        let file = replay_file(&level_file);
+                        if !file.is_file() {
+                            info!("There is no replay of level {:?} yet.", level_name);
+                            return Trans::None;
+                        }
+                        match Replay::load(&file) {
+                            Ok(replay) => {
+                                Trans::Push(Box::new(PlayState::replay(level_file, replay)))
+                            }
+                            Err(err) => {
+                                error!("Failed to load replay {:?}: {:?}", file, err);
+                                Trans::None
+                            }
+                        }
+                    }
+                    _ => Trans::None,
+                }
+            },
+        )
+    }
+
     /// Prepare to start or resume.
     fn perform_setup(&self, world: &mut World) {
         UiHandles::add_ui(&UiType::Fps, world);
@@ -139,6 +174,9 @@ impl SimpleState for LevelSelectState {
                     key_code: VirtualKeyCode::Return,
                     scancode: _,
                 } => Self::select_node(data.world),
+                InputEvent::ActionPressed(action) if action == "watch_replay" => {
+                    Self::watch_replay(data.world)
+                }
                 _ => Trans::None,
             },
         }
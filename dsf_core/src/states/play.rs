@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use amethyst::prelude::WorldExt;
 
@@ -23,14 +23,24 @@ use crate::resources::*;
 use crate::states::window_event_handler;
 use crate::systems;
 use crate::systems::SoundEvent;
-use crate::utility::files::get_levels_dir;
+use crate::utility::files::{get_levels_dir, get_replays_dir};
 use amethyst::core::ecs::{Dispatcher, DispatcherBuilder};
 use amethyst::core::shrev::EventChannel;
 use amethyst::core::SystemExt;
+use amethyst::prelude::Config;
 
 pub struct PlayState {
     dispatcher: Dispatcher<'static, 'static>,
     level_file: PathBuf,
+    /// If this is set, the level plays out this recording instead of responding to the controls.
+    replay: Option<Replay>,
+    /// How many seconds of game time have passed since the level was (re)started.
+    elapsed_seconds: f32,
+    /// Set to true once the player completed the level and it was recorded in the SaveGame.
+    completion_recorded: bool,
+    /// Whether completing the level counts towards the player's progress. Only levels that the
+    /// player picked from an adventure do. Demos, replays and play-tests from the editor do not.
+    counts_for_progress: bool,
 }
 
 impl<'a, 'b> PlayState {
@@ -40,10 +50,32 @@ impl<'a, 'b> PlayState {
         PlayState::new(level_file)
     }
 
+    /// Creates a PlayState that plays back the given recording in the given level.
+    pub fn replay(level_file: PathBuf, replay: Replay) -> Self {
+        PlayState {
+            replay: Some(replay),
+            ..PlayState::new(level_file)
+        }
+    }
+
+    /// Creates a PlayState for a level that the player picked from an adventure. Completing it is
+    /// recorded in the SaveGame.
+    pub fn from_adventure(level_file: PathBuf) -> Self {
+        PlayState {
+            counts_for_progress: true,
+            ..PlayState::new(level_file)
+        }
+    }
+
     /// Creates a new PlayState that will load the given level.
+    /// Completing the level does not count towards the player's progress.
     pub fn new(level_file: PathBuf) -> Self {
         PlayState {
             level_file,
+            replay: None,
+            elapsed_seconds: 0.,
+            completion_recorded: false,
+            counts_for_progress: false,
             dispatcher: DispatcherBuilder::new()
                 .with(
                     systems::PlayerSystem::default().pausable(CurrentState::Running),
@@ -71,16 +103,40 @@ impl<'a, 'b> PlayState {
                     &["player_system"],
                 )
                 .with(
-                    systems::RewindSystem.pausable(CurrentState::Rewinding),
+                    systems::RewindSystem,
                     "rewind_system",
-                    &["rewind_control_system"],
+                    &["rewind_control_system", "velocity_system"],
                 )
                 .with(systems::DebugSystem, "debug_system", &[])
-                .with(systems::KeyCollectionSystem, "key_collection_system", &[])
-                .with(systems::PickupSystem, "pickup_system", &[])
-                .with(systems::UseToolSystem, "use_tool_system", &[])
+                .with(
+                    systems::KeyCollectionSystem.pausable(CurrentState::Running),
+                    "key_collection_system",
+                    &[],
+                )
+                .with(
+                    systems::PickupSystem.pausable(CurrentState::Running),
+                    "pickup_system",
+                    &[],
+                )
+                .with(
+                    systems::UseToolSystem.pausable(CurrentState::Running),
+                    "use_tool_system",
+                    &["player_system"],
+                )
                 .with(systems::LevelWrappingSystem, "level_wrapping_system", &[])
                 .with(systems::WinSystem, "win_system", &[])
+                .with(
+                    systems::HistorySystem.pausable(CurrentState::Running),
+                    "history_system",
+                    &[
+                        "rewind_system",
+                        "velocity_system",
+                        "key_collection_system",
+                        "pickup_system",
+                        "use_tool_system",
+                        "level_wrapping_system",
+                    ],
+                )
                 .build(),
         }
     }
@@ -96,6 +152,13 @@ impl<'a, 'b> PlayState {
             info!("Slowing down time, from x{:?} to x{:?}. This feature exists for debugging purposes only.", old_scale, new_scale);
             self.update_time_scale(world, new_scale);
             Trans::None
+        } else if action == "undo" {
+            if world.read_resource::<ReplayMode>().is_active() {
+                info!("Cannot undo while the input is being recorded or played back.");
+            } else {
+                world.write_resource::<Rewind>().undo_requested = true;
+            }
+            Trans::None
         } else if action == "restart" {
             world
                 .write_resource::<EventChannel<SoundEvent>>()
@@ -111,12 +174,93 @@ impl<'a, 'b> PlayState {
         world.write_resource::<Time>().set_time_scale(time_scale);
     }
 
-    fn reset_level(&self, world: &mut World) {
+    fn reset_level(&mut self, world: &mut World) {
+        self.elapsed_seconds = 0.;
+        self.completion_recorded = false;
+        save_recording(world);
         world.delete_all();
         UiHandles::add_ui(&UiType::Fps, world);
         UiHandles::add_ui(&UiType::Play, world);
         create_camera(world);
         load_level(&self.level_file, world).expect("Failed to load level!");
+        let replay_mode = self.start_replay_mode(world);
+        world.insert(replay_mode);
+    }
+
+    /// Keeps track of the time spent in the level. When the player completes the level, records
+    /// their results in the SaveGame.
+    fn track_progress(&mut self, world: &mut World) {
+        if self.completion_recorded {
+            return;
+        }
+        if !world.read_resource::<WinCondition>().reached_open_door {
+            self.elapsed_seconds += world.read_resource::<Time>().fixed_seconds();
+            return;
+        }
+        self.completion_recorded = true;
+        if !self.counts_for_progress {
+            return;
+        }
+        let level_name = self
+            .level_file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let moves = world.read_resource::<History>().nr_moves();
+        info!(
+            "Completed level {:?} in {:.2} seconds and {} moves.",
+            level_name, self.elapsed_seconds, moves
+        );
+        let mut save_game = world.write_resource::<SaveGame>();
+        save_game.record_completion(level_name, self.elapsed_seconds, moves);
+        save_game.save();
+    }
+
+    /// Decides whether to play back a recording, record the player's input, or do neither.
+    fn start_replay_mode(&self, world: &mut World) -> ReplayMode {
+        let level_hash = hash_level_file(&self.level_file).unwrap_or_else(|err| {
+            error!("Failed to hash level file {:?}: {:?}", self.level_file, err);
+            String::new()
+        });
+        if let Some(replay) = &self.replay {
+            if replay.level_hash != level_hash {
+                warn!(
+                    "The level {:?} has changed since this replay was recorded. \
+                    The replay will probably not play out as it did back then.",
+                    self.level_file
+                );
+            }
+            info!("Playing back a replay of {} ticks.", replay.nr_ticks());
+            ReplayMode::play_back(replay.clone())
+        } else if world.read_resource::<DebugSettings>().record_replays {
+            ReplayMode::Recording(Replay::new(self.level_file.clone(), level_hash))
+        } else {
+            ReplayMode::Off
+        }
+    }
+}
+
+/// Returns the file that the replays of the given level are saved to. Only the most recent replay
+/// of each level is kept.
+pub fn replay_file(level_file: &Path) -> PathBuf {
+    let level_name = level_file
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    get_replays_dir().join(format!("{}.ron", level_name))
+}
+
+/// If the player's input was being recorded, saves the recording and stops recording.
+fn save_recording(world: &mut World) {
+    let replay_mode = std::mem::take(&mut *world.write_resource::<ReplayMode>());
+    if let ReplayMode::Recording(replay) = replay_mode {
+        if replay.nr_ticks() > 0 {
+            let file = replay_file(&replay.level_file);
+            match replay.write(&file) {
+                Ok(()) => info!("Saved replay to {:?}", file),
+                Err(err) => error!("Failed to save replay to {:?}: {:?}", file, err),
+            }
+        }
     }
 }
 
@@ -129,6 +273,7 @@ impl SimpleState for PlayState {
 
     fn on_stop(&mut self, data: StateData<GameData>) {
         info!("PlayState on_stop");
+        save_recording(data.world);
         data.world.delete_all();
     }
 
@@ -159,6 +304,7 @@ impl SimpleState for PlayState {
 
     fn fixed_update(&mut self, data: StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
         self.dispatcher.dispatch(&data.world);
+        self.track_progress(data.world);
         Trans::None
     }
 
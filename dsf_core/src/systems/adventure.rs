@@ -1,6 +1,8 @@
 use crate::components::{Direction2D, MapCursor};
+use crate::levels::LevelMetadata;
 use crate::resources::{
-    Adventure, AdventureNode, MapElement, MovementConfig, NodeDetails, PositionOnMap, SoundType,
+    Adventure, AdventureNode, LevelProgress, MapElement, MovementConfig, NodeDetails,
+    PositionOnMap, SaveGame, SoundType,
 };
 use crate::systems::SoundEvent;
 use amethyst::core::ecs::{Join, Read, System, Write, WriteStorage};
@@ -90,7 +92,7 @@ fn move_cursor(
         pos_on_map.pos.append_y(direction.y.signum_i())
     };
 
-    if adventure.nodes.contains_key(&target_pos) {
+    if adventure.is_reachable(&target_pos) {
         pos_on_map.pos = target_pos;
         transform.set_translation_x(pos_on_map.pos.x as f32 + 0.5);
         transform.set_translation_y(pos_on_map.pos.y as f32 + 0.5);
@@ -98,8 +100,8 @@ fn move_cursor(
     }
 }
 
-/// Updates the UI label on the adventure and level select screen. The label must always display the
-/// name of the currently selected node.
+/// Updates the UI labels on the adventure and level select screen. The labels must always display
+/// the title and other details of the currently selected node.
 pub struct LevelSelectUiUpdateSystem;
 
 impl<'s> System<'s> for LevelSelectUiUpdateSystem {
@@ -109,27 +111,82 @@ impl<'s> System<'s> for LevelSelectUiUpdateSystem {
         UiFinder<'s>,
         Read<'s, Adventure>,
         Read<'s, PositionOnMap>,
+        Read<'s, SaveGame>,
     );
 
-    fn run(&mut self, (mut ui_text, finder, adventure, pos_on_map): Self::SystemData) {
-        let label_title = {
-            let label_title_entity = finder.find("label_node_title");
-            if let Some(fps_entity) = label_title_entity {
-                ui_text.get_mut(fps_entity)
-            } else {
-                None
-            }
+    fn run(&mut self, (mut ui_text, finder, adventure, pos_on_map, save_game): Self::SystemData) {
+        let selected = adventure.nodes.get(&pos_on_map.pos);
+        let (file_name, metadata, progress) = match selected {
+            Some(MapElement::Node(AdventureNode {
+                details: NodeDetails::Level(file_name),
+                metadata,
+                ..
+            })) => (
+                file_name.as_str(),
+                metadata.clone().unwrap_or_default(),
+                progress_line(save_game.progress(file_name)),
+            ),
+            _ => ("Nothing", LevelMetadata::default(), String::new()),
+        };
+        set_label(
+            "label_node_title",
+            format!("Selected: {}", metadata.title_or(file_name)),
+            &mut ui_text,
+            &finder,
+        );
+        set_label(
+            "label_node_details",
+            details_line(&metadata),
+            &mut ui_text,
+            &finder,
+        );
+        set_label(
+            "label_node_description",
+            metadata.description.clone(),
+            &mut ui_text,
+            &finder,
+        );
+        let hint = if metadata.hint.is_empty() {
+            String::new()
+        } else {
+            format!("Hint: {}", metadata.hint)
         };
-        if let Some(mut label_title) = label_title {
-            let selected = adventure.nodes.get(&pos_on_map.pos);
-            let selected_title = match selected {
-                Some(MapElement::Node(AdventureNode {
-                    details: NodeDetails::Level(file_name),
-                    ..
-                })) => file_name,
-                _ => "Nothing",
-            };
-            label_title.text = format!("Selected: {:?}", selected_title);
+        set_label("label_node_hint", hint, &mut ui_text, &finder);
+        set_label("label_node_progress", progress, &mut ui_text, &finder);
+    }
+}
+
+/// Sums up the player's best results in a level, or says they have not completed it yet.
+fn progress_line(progress: Option<&LevelProgress>) -> String {
+    match progress {
+        Some(progress) => format!(
+            "Completed  -  Best time: {:.1} s  -  Fewest moves: {}",
+            progress.best_time, progress.best_moves
+        ),
+        None => "Not completed yet".to_string(),
+    }
+}
+
+/// Sums up the author, difficulty and par of a level in a single line.
+/// Leaves out whatever the level does not specify.
+fn details_line(metadata: &LevelMetadata) -> String {
+    let mut details = Vec::new();
+    if !metadata.author.is_empty() {
+        details.push(format!("By {}", metadata.author));
+    }
+    if let Some(difficulty) = metadata.difficulty {
+        details.push(difficulty.to_string());
+    }
+    if let Some(par) = metadata.par_moves {
+        details.push(format!("Par: {} moves", par));
+    }
+    details.join("  -  ")
+}
+
+fn set_label(id: &str, text: String, ui_text: &mut WriteStorage<UiText>, finder: &UiFinder) {
+    if let Some(entity) = finder.find(id) {
+        if let Some(label) = ui_text.get_mut(entity) {
+            label.text = text;
         }
     }
 }
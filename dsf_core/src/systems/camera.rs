@@ -1,31 +1,68 @@
 use crate::components::*;
+use crate::entities::CAMERA_SCALE;
+use crate::resources::{SignalEdge, SignalEdgeDetector, TileMap, WorldBounds};
 
 use amethyst::{
     core::transform::Transform,
     core::{
-        math::{partial_clamp, Vector2},
+        math::{partial_clamp, Vector2, Vector3},
         timing::Time,
     },
-    ecs::prelude::{Join, Read, ReadStorage, System, WriteStorage},
+    ecs::prelude::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
     input::{InputHandler, StringBindings},
+    window::ScreenDimensions,
 };
 
 /// This system handles player input to control certain aspects of the camera.
-/// Specifically: camera panning, camera zoom.
+/// Specifically: camera panning, camera zoom, toggling the fit-whole-level mode and switching
+/// between the edge modes.
+///
+/// It also moves the zoom factor smoothly towards its target.
 pub struct CameraControlSystem;
 
 impl<'s> System<'s> for CameraControlSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'s, CameraFrame>,
+        WriteStorage<'s, Transform>,
+        Write<'s, SignalEdgeDetector>,
         Read<'s, InputHandler<StringBindings>>,
         Read<'s, Time>,
     );
 
-    fn run(&mut self, (mut camera_frames, input, time): Self::SystemData) {
-        let _zoom = input.axis_value("zoom").unwrap_or(0.0);
+    fn run(&mut self, (mut camera_frames, mut transforms, mut sed, input, time): Self::SystemData) {
+        let zoom = input.axis_value("zoom").unwrap_or(0.0);
+        let toggle_fit_level = matches!(sed.edge("toggle_fit_level", &input), SignalEdge::Rising);
+        let toggle_edge_mode = matches!(
+            sed.edge("toggle_camera_edge_mode", &input),
+            SignalEdge::Rising
+        );
         let pan_x = input.axis_value("pan_x").unwrap_or(0.0);
         let pan_y = input.axis_value("pan_y").unwrap_or(0.0);
-        for frame in (&mut camera_frames).join() {
+        for (frame, transform) in (&mut camera_frames, &mut transforms).join() {
+            if toggle_fit_level {
+                frame.fit_level ^= true;
+                if !frame.fit_level {
+                    frame.target_zoom = 1.;
+                }
+            }
+            if toggle_edge_mode {
+                frame.edge_mode = frame.edge_mode.next();
+                info!("Camera edge mode is now {:?}.", frame.edge_mode);
+            }
+            if zoom.abs() > f32::EPSILON {
+                frame.fit_level = false;
+                frame.target_zoom = *partial_clamp(
+                    &(frame.target_zoom * (1. + zoom * frame.zoom_step)),
+                    &frame.min_zoom,
+                    &frame.max_zoom,
+                )
+                .expect("Oh noes!");
+            }
+            let zoom_progress = (frame.zoom_speed * time.delta_seconds()).min(1.);
+            frame.zoom += (frame.target_zoom - frame.zoom) * zoom_progress;
+            let scale = CAMERA_SCALE / frame.zoom;
+            transform.set_scale(Vector3::new(scale, scale, 1.0));
             if pan_x.abs() < f32::EPSILON && pan_y.abs() < f32::EPSILON {
                 if frame.pan.magnitude() > f32::EPSILON {
                     // Recovery (jump back to zero pan)
@@ -54,26 +91,133 @@ impl<'s> System<'s> for CameraControlSystem {
     }
 }
 
-/// This system updates the camera frame position to center on the player's position.
+/// This system moves the camera frame smoothly towards the player's position.
+/// If the camera is in fit-whole-level mode, it centers on the level instead.
+///
+/// Levels wrap around. In the Wrap edge mode, the camera follows the player across the edge of the
+/// level, rather than sweeping across the entire level. In the Clamp edge mode, the camera stays
+/// inside the level.
+///
+/// Only does anything if there is a player. The editor positions its camera itself.
 pub struct CameraSystem;
 
 impl<'s> System<'s> for CameraSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         ReadStorage<'s, Player>,
-        ReadStorage<'s, CameraFrame>,
+        WriteStorage<'s, CameraFrame>,
         WriteStorage<'s, Transform>,
+        Read<'s, TileMap>,
+        Read<'s, Time>,
+        ReadExpect<'s, ScreenDimensions>,
     );
 
-    fn run(&mut self, (players, camera_frames, mut transforms): Self::SystemData) {
+    fn run(
+        &mut self,
+        (players, mut camera_frames, mut transforms, tile_map, time, screen_dimens): Self::SystemData,
+    ) {
         let maybe_player_pos = (&players, &transforms)
             .join()
-            .map(|(_, transform)| (transform.translation().x, transform.translation().y))
+            .map(|(_, transform)| {
+                Vector2::new(transform.translation().x, transform.translation().y)
+            })
             .next();
-        if let Some((player_x, player_y)) = maybe_player_pos {
-            for (frame, transform) in (&camera_frames, &mut transforms).join() {
-                transform.set_translation_x(player_x + frame.pan.x);
-                transform.set_translation_y(player_y + frame.pan.y);
+        if let Some(player_pos) = maybe_player_pos {
+            let bounds = &tile_map.world_bounds;
+            for (frame, transform) in (&mut camera_frames, &mut transforms).join() {
+                if frame.fit_level {
+                    fit_to_bounds(frame, transform, bounds, &screen_dimens);
+                    frame.focus = Some(Vector2::new(
+                        transform.translation().x,
+                        transform.translation().y,
+                    ));
+                    continue;
+                }
+                let target = player_pos + frame.pan;
+                let mut focus = frame.focus.unwrap_or(target);
+                if frame.edge_mode == EdgeMode::Wrap {
+                    focus = follow_wrap(focus, &target, bounds);
+                }
+                let follow_progress = (frame.follow_speed * time.delta_seconds()).min(1.);
+                focus += (target - focus) * follow_progress;
+                if frame.edge_mode == EdgeMode::Clamp {
+                    focus = clamp_to_bounds(focus, frame.zoom, bounds, &screen_dimens);
+                }
+                frame.focus = Some(focus);
+                transform.set_translation_x(focus.x);
+                transform.set_translation_y(focus.y);
             }
         }
     }
 }
+
+/// If the target is more than half a level away from the focus along an axis, the player must
+/// have wrapped around the level. Moves the focus across the level along with the player, so the
+/// camera keeps the same position relative to the player.
+fn follow_wrap(focus: Vector2<f32>, target: &Vector2<f32>, bounds: &WorldBounds) -> Vector2<f32> {
+    Vector2::new(
+        wrap_axis(focus.x, target.x, bounds.width() as f32),
+        wrap_axis(focus.y, target.y, bounds.height() as f32),
+    )
+}
+
+fn wrap_axis(focus: f32, target: f32, level_size: f32) -> f32 {
+    if target - focus > level_size * 0.5 {
+        focus + level_size
+    } else if focus - target > level_size * 0.5 {
+        focus - level_size
+    } else {
+        focus
+    }
+}
+
+/// Moves the focus so the camera does not show anything outside the bounds. If the bounds are
+/// smaller than the screen along an axis, centers the camera on the bounds along that axis.
+fn clamp_to_bounds(
+    focus: Vector2<f32>,
+    zoom: f32,
+    bounds: &WorldBounds,
+    screen_dimens: &ScreenDimensions,
+) -> Vector2<f32> {
+    // At zoom factor 1.0, one meter in the world takes up 1 / CAMERA_SCALE pixels.
+    let half_view_width = screen_dimens.width() * CAMERA_SCALE / zoom * 0.5;
+    let half_view_height = screen_dimens.height() * CAMERA_SCALE / zoom * 0.5;
+    Vector2::new(
+        clamp_axis(
+            focus.x,
+            half_view_width,
+            bounds.x() as f32,
+            bounds.upper_x() as f32,
+        ),
+        clamp_axis(
+            focus.y,
+            half_view_height,
+            bounds.y() as f32,
+            bounds.upper_y() as f32,
+        ),
+    )
+}
+
+fn clamp_axis(focus: f32, half_view: f32, lower: f32, upper: f32) -> f32 {
+    if upper - lower <= half_view * 2. {
+        (lower + upper) * 0.5
+    } else {
+        focus.max(lower + half_view).min(upper - half_view)
+    }
+}
+
+/// Centers the camera frame on the given bounds and sets the target zoom factor so that the
+/// bounds fill the screen as far as possible while staying entirely visible.
+pub fn fit_to_bounds(
+    frame: &mut CameraFrame,
+    transform: &mut Transform,
+    bounds: &WorldBounds,
+    screen_dimens: &ScreenDimensions,
+) {
+    transform.set_translation_x(bounds.x() as f32 + bounds.width() as f32 * 0.5);
+    transform.set_translation_y(bounds.y() as f32 + bounds.height() as f32 * 0.5);
+    // At zoom factor 1.0, one meter in the world takes up 1 / CAMERA_SCALE pixels.
+    let zoom_x = screen_dimens.width() * CAMERA_SCALE / bounds.width() as f32;
+    let zoom_y = screen_dimens.height() * CAMERA_SCALE / bounds.height() as f32;
+    frame.target_zoom = zoom_x.min(zoom_y);
+}
@@ -1,6 +1,7 @@
 use crate::components::{Steering, SteeringMode};
-use crate::resources::TileMap;
+use crate::resources::{TileMap, WorldBounds};
 use amethyst::core::ecs::{Join, Read, System, WriteStorage};
+use amethyst::core::math::Vector2;
 use amethyst::core::Transform;
 
 /// Implements wrapping behaviour for levels.
@@ -18,46 +19,56 @@ impl<'s> System<'s> for LevelWrappingSystem {
 
     fn run(&mut self, (mut steerings, mut transforms, tile_map): Self::SystemData) {
         for (transform, steering) in (&mut transforms, &mut steerings).join() {
-            if transform.translation().x < tile_map.world_bounds.x() as f32 {
-                transform.set_translation_x(
-                    transform.translation().x + tile_map.world_bounds.width() as f32,
-                );
-                steering.pos.x += tile_map.world_bounds.width();
-                steering.destination.x += tile_map.world_bounds.width();
-            } else if transform.translation().x > (tile_map.world_bounds.upper_x()) as f32 {
-                transform.set_translation_x(
-                    transform.translation().x - tile_map.world_bounds.width() as f32,
-                );
-                steering.pos.x -= tile_map.world_bounds.width();
-                steering.destination.x -= tile_map.world_bounds.width();
-            }
+            let mut translation =
+                Vector2::new(transform.translation().x, transform.translation().y);
+            wrap_around(&mut translation, steering, &tile_map.world_bounds);
+            transform.set_translation_x(translation.x);
+            transform.set_translation_y(translation.y);
+        }
+    }
+}
 
-            if transform.translation().y < tile_map.world_bounds.y() as f32 {
-                transform.set_translation_y(
-                    transform.translation().y + tile_map.world_bounds.height() as f32,
-                );
-                steering.pos.y += tile_map.world_bounds.height();
-                steering.destination.y += tile_map.world_bounds.height();
-                // Ignore warning, we'll want to add more patterns in the future.
-                #[allow(clippy::single_match)]
-                match steering.mode {
-                    SteeringMode::Falling {
-                        x_movement,
-                        starting_y_pos,
-                        duration,
-                    } => {
-                        steering.mode = SteeringMode::Falling {
-                            x_movement,
-                            starting_y_pos: starting_y_pos + tile_map.world_bounds.height() as f32,
-                            duration,
-                        }
-                    }
-                    _ => (),
-                };
-            } else if transform.translation().y > (tile_map.world_bounds.upper_y()) as f32 {
-                steering.pos.y -= tile_map.world_bounds.height();
-                steering.destination.y -= tile_map.world_bounds.height();
+/// If the entity has moved past one of the world's borders, teleports it to the other side of
+/// the world.
+/// This function does not touch the ECS, so it is shared by the LevelWrappingSystem and the
+/// headless simulation.
+pub fn wrap_around(
+    translation: &mut Vector2<f32>,
+    steering: &mut Steering,
+    world_bounds: &WorldBounds,
+) {
+    if translation.x < world_bounds.x() as f32 {
+        translation.x += world_bounds.width() as f32;
+        steering.pos.x += world_bounds.width();
+        steering.destination.x += world_bounds.width();
+    } else if translation.x > (world_bounds.upper_x()) as f32 {
+        translation.x -= world_bounds.width() as f32;
+        steering.pos.x -= world_bounds.width();
+        steering.destination.x -= world_bounds.width();
+    }
+
+    if translation.y < world_bounds.y() as f32 {
+        translation.y += world_bounds.height() as f32;
+        steering.pos.y += world_bounds.height();
+        steering.destination.y += world_bounds.height();
+        // Ignore warning, we'll want to add more patterns in the future.
+        #[allow(clippy::single_match)]
+        match steering.mode {
+            SteeringMode::Falling {
+                x_movement,
+                starting_y_pos,
+                duration,
+            } => {
+                steering.mode = SteeringMode::Falling {
+                    x_movement,
+                    starting_y_pos: starting_y_pos + world_bounds.height() as f32,
+                    duration,
+                }
             }
-        }
+            _ => (),
+        };
+    } else if translation.y > (world_bounds.upper_y()) as f32 {
+        steering.pos.y -= world_bounds.height();
+        steering.destination.y -= world_bounds.height();
     }
 }
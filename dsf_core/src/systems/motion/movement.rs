@@ -2,6 +2,7 @@ use crate::components::*;
 
 use crate::resources::*;
 
+use amethyst::core::math::Vector2;
 use amethyst::core::num::FloatConst;
 use amethyst::{
     core::timing::Time,
@@ -22,14 +23,21 @@ impl<'s> System<'s> for VelocitySystem {
 
     fn run(&mut self, (mut transforms, velocities, time): Self::SystemData) {
         for (transform, velocity) in (&mut transforms, &velocities).join() {
-            transform
-                .set_translation_x(transform.translation().x + time.fixed_seconds() * velocity.x);
-            transform
-                .set_translation_y(transform.translation().y + time.fixed_seconds() * velocity.y);
+            let mut translation =
+                Vector2::new(transform.translation().x, transform.translation().y);
+            apply_velocity(&mut translation, velocity, time.fixed_seconds());
+            transform.set_translation_x(translation.x);
+            transform.set_translation_y(translation.y);
         }
     }
 }
 
+/// Moves the given translation according to the velocity for the given amount of time.
+pub fn apply_velocity(translation: &mut Vector2<f32>, velocity: &Velocity, delta_seconds: f32) {
+    translation.x += delta_seconds * velocity.x;
+    translation.y += delta_seconds * velocity.y;
+}
+
 /// Sets velocity for all entities with steering.
 pub struct MovementSystem;
 
@@ -56,58 +64,71 @@ impl<'s> System<'s> for MovementSystem {
                 0.
             });
 
-            let (centered_x, centered_y) = steering.to_centered_coords(steering.pos);
-            let (desired_pos_x, desired_pos_y) = steering.to_centered_coords(steering.destination);
-            match steering.mode {
-                SteeringMode::Grounded => {
-                    // If grounded, correct y translation and zero out y velocity.
-                    transform.set_translation_y(centered_y);
-                    velocity.y = 0.0;
-                }
-                SteeringMode::Climbing => {
-                    // If climbing, correct x translation and zero out x velocity.
-                    transform.set_translation_x(centered_x);
-                    velocity.x = 0.0;
-                    // If climbing:
-                    let delta = desired_pos_y - transform.translation().y;
-                    if steering.facing.y.aligns_with(delta) {
-                        velocity.y = steering.facing.y.signum() * config.player_speed;
-                    } else {
-                        velocity.y = 0.0;
-                        transform.set_translation_y(centered_y);
-                    }
-                }
-                SteeringMode::Falling {
-                    starting_y_pos,
-                    duration,
-                    ..
-                } => {
-                    // Set y-position directly, based on movement function. We don't use velocity for this.
-                    velocity.y = 0.0;
-                    transform
-                        .set_translation_y(starting_y_pos + steering.mode.calc_delta_y(duration));
-                }
-                SteeringMode::Jumping {
-                    starting_y_pos,
-                    duration,
-                    ..
-                } => {
-                    // Set y-position directly, based on movement function. We don't use velocity for this.
-                    velocity.y = 0.0;
-                    transform
-                        .set_translation_y(starting_y_pos + steering.mode.calc_delta_y(duration));
-                }
-            }
+            let mut translation =
+                Vector2::new(transform.translation().x, transform.translation().y);
+            apply_steering(steering, &mut translation, velocity, &config);
+            transform.set_translation_x(translation.x);
+            transform.set_translation_y(translation.y);
+        }
+    }
+}
 
-            // Set x-velocity based on current and desired position.
-            // If necessary, adjust x-position, snap to grid.
-            let delta = desired_pos_x - transform.translation().x;
-            if steering.facing.x.aligns_with(delta) {
-                velocity.x = steering.facing.x.signum() * config.player_speed;
+/// Sets the velocity of a single entity based on its steering. Where necessary, the translation is
+/// corrected directly; to snap the entity to the grid or to follow the jumping and falling curves.
+pub fn apply_steering(
+    steering: &Steering,
+    translation: &mut Vector2<f32>,
+    velocity: &mut Velocity,
+    config: &MovementConfig,
+) {
+    let (centered_x, centered_y) = steering.to_centered_coords(steering.pos);
+    let (desired_pos_x, desired_pos_y) = steering.to_centered_coords(steering.destination);
+    match steering.mode {
+        SteeringMode::Grounded => {
+            // If grounded, correct y translation and zero out y velocity.
+            translation.y = centered_y;
+            velocity.y = 0.0;
+        }
+        SteeringMode::Climbing => {
+            // If climbing, correct x translation and zero out x velocity.
+            translation.x = centered_x;
+            velocity.x = 0.0;
+            // If climbing:
+            let delta = desired_pos_y - translation.y;
+            if steering.facing.y.aligns_with(delta) {
+                velocity.y = steering.facing.y.signum() * config.player_speed;
             } else {
-                velocity.x = 0.0;
-                transform.set_translation_x(centered_x);
+                velocity.y = 0.0;
+                translation.y = centered_y;
             }
         }
+        SteeringMode::Falling {
+            starting_y_pos,
+            duration,
+            ..
+        } => {
+            // Set y-position directly, based on movement function. We don't use velocity for this.
+            velocity.y = 0.0;
+            translation.y = starting_y_pos + steering.mode.calc_delta_y(duration);
+        }
+        SteeringMode::Jumping {
+            starting_y_pos,
+            duration,
+            ..
+        } => {
+            // Set y-position directly, based on movement function. We don't use velocity for this.
+            velocity.y = 0.0;
+            translation.y = starting_y_pos + steering.mode.calc_delta_y(duration);
+        }
+    }
+
+    // Set x-velocity based on current and desired position.
+    // If necessary, adjust x-position, snap to grid.
+    let delta = desired_pos_x - translation.x;
+    if steering.facing.x.aligns_with(delta) {
+        velocity.x = steering.facing.x.signum() * config.player_speed;
+    } else {
+        velocity.x = 0.0;
+        translation.x = centered_x;
     }
 }
@@ -1,10 +1,14 @@
 use crate::components::{Direction1D, Player, Steering, SteeringIntent};
-use crate::resources::MovementConfig;
-use amethyst::core::ecs::{Join, Read, ReadStorage, System, WriteStorage};
+use crate::resources::{MovementConfig, ReplayMode};
+use crate::sim::PlayerInput;
+use amethyst::core::ecs::{Join, Read, ReadStorage, System, Write, WriteStorage};
 use amethyst::core::Time;
 use amethyst::input::{InputHandler, StringBindings};
 
 /// Sets the player intention to move.
+///
+/// The input for this tick is taken from the ReplayMode, so it can be recorded or played back.
+/// It is then stored in the PlayerInput resource, for other systems that respond to the controls.
 #[derive(Default)]
 pub struct PlayerSystem;
 
@@ -15,68 +19,107 @@ impl<'s> System<'s> for PlayerSystem {
         ReadStorage<'s, Steering>,
         WriteStorage<'s, SteeringIntent>,
         Read<'s, InputHandler<StringBindings>>,
+        Write<'s, ReplayMode>,
+        Write<'s, PlayerInput>,
         Read<'s, MovementConfig>,
         Read<'s, Time>,
     );
 
     fn run(
         &mut self,
-        (mut players, steerings, mut steering_intents, input, config, time): Self::SystemData,
+        (
+            mut players,
+            steerings,
+            mut steering_intents,
+            input,
+            mut replay_mode,
+            mut current_input,
+            config,
+            time,
+        ): Self::SystemData,
     ) {
-        let input_x = input.axis_value("move_x").unwrap_or(0.0);
-        let input_y = input.axis_value("move_y").unwrap_or(0.0);
-        let jump_down = input.action_is_down("jump").unwrap_or(false);
+        let player_input = replay_mode.next_input(read_player_input(&input));
+        *current_input = player_input;
         for (player, intent, steering) in (&mut players, &mut steering_intents, &steerings).join() {
-            let initiate_jump = jump_down && !player.pressing_jump;
-            player.pressing_jump = jump_down;
-            player.jump_grace_timer = if initiate_jump {
-                Some(0.)
-            } else if let Some(time_passed) = player.jump_grace_timer {
-                let time_passed = time_passed + time.fixed_seconds();
-                if time_passed < config.jump_allowance {
-                    Some(time_passed)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-            let old_walk = intent.walk;
-            let new_walk = Direction1D::new(input_x);
-            let turn_around = steering.is_grounded()
-                && steering.facing.x.is_opposite(&new_walk)
-                && old_walk.is_neutral();
-            player.turn_around_timer = if turn_around {
-                // Player wants to turn around, initialise turn-around timer.
-                Some(0.)
-            } else if new_walk.is_neutral() {
-                // Player has let go of controls, forcefully reset timer.
-                None
-            } else if let Some(time_passed) = player.turn_around_timer {
-                let time_passed = time_passed + time.fixed_seconds();
-                if time_passed < config.turn_allowance {
-                    Some(time_passed)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+            update_player_intent(
+                player,
+                intent,
+                steering,
+                &player_input,
+                &config,
+                time.fixed_seconds(),
+            );
+        }
+    }
+}
 
-            if player.turn_around_timer.is_none() {
-                intent.walk = new_walk;
-            }
-            intent.face = new_walk;
-            if intent.walk_invalidated && old_walk != intent.walk {
-                intent.walk_invalidated = false;
-            }
-            intent.climb = Direction1D::new(input_y);
-            intent.jump = player.equipped.is_none() && initiate_jump;
-            intent.jump_direction = if player.jump_grace_timer.is_some() {
-                intent.walk
-            } else {
-                Direction1D::Neutral
-            };
+/// Reads the current state of the player controls from the input handler.
+pub fn read_player_input(input: &InputHandler<StringBindings>) -> PlayerInput {
+    PlayerInput {
+        move_x: input.axis_value("move_x").unwrap_or(0.0),
+        move_y: input.axis_value("move_y").unwrap_or(0.0),
+        jump: input.action_is_down("jump").unwrap_or(false),
+    }
+}
+
+/// Translates the player's input into an intent to move.
+/// This function does not touch the ECS, so it is shared by the PlayerSystem and the headless
+/// simulation.
+pub fn update_player_intent(
+    player: &mut Player,
+    intent: &mut SteeringIntent,
+    steering: &Steering,
+    input: &PlayerInput,
+    config: &MovementConfig,
+    delta_seconds: f32,
+) {
+    let initiate_jump = input.jump && !player.pressing_jump;
+    player.pressing_jump = input.jump;
+    player.jump_grace_timer = if initiate_jump {
+        Some(0.)
+    } else if let Some(time_passed) = player.jump_grace_timer {
+        let time_passed = time_passed + delta_seconds;
+        if time_passed < config.jump_allowance {
+            Some(time_passed)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+    let old_walk = intent.walk;
+    let new_walk = Direction1D::new(input.move_x);
+    let turn_around =
+        steering.is_grounded() && steering.facing.x.is_opposite(&new_walk) && old_walk.is_neutral();
+    player.turn_around_timer = if turn_around {
+        // Player wants to turn around, initialise turn-around timer.
+        Some(0.)
+    } else if new_walk.is_neutral() {
+        // Player has let go of controls, forcefully reset timer.
+        None
+    } else if let Some(time_passed) = player.turn_around_timer {
+        let time_passed = time_passed + delta_seconds;
+        if time_passed < config.turn_allowance {
+            Some(time_passed)
+        } else {
+            None
         }
+    } else {
+        None
+    };
+
+    if player.turn_around_timer.is_none() {
+        intent.walk = new_walk;
+    }
+    intent.face = new_walk;
+    if intent.walk_invalidated && old_walk != intent.walk {
+        intent.walk_invalidated = false;
     }
+    intent.climb = Direction1D::new(input.move_y);
+    intent.jump = player.equipped.is_none() && initiate_jump;
+    intent.jump_direction = if player.jump_grace_timer.is_some() {
+        intent.walk
+    } else {
+        Direction1D::Neutral
+    };
 }
@@ -2,6 +2,7 @@ use crate::components::*;
 use crate::resources::*;
 use crate::systems::SoundEvent;
 use amethyst::core::ecs::shrev::EventChannel;
+use amethyst::core::math::Vector2;
 use amethyst::core::{Time, Transform};
 use amethyst::ecs::prelude::{Join, Read, ReadStorage, System, Write, WriteStorage};
 
@@ -16,7 +17,6 @@ impl<'s> System<'s> for SteeringSystem {
         ReadStorage<'s, Transform>,
         WriteStorage<'s, Steering>,
         Read<'s, TileMap>,
-        Write<'s, History>,
         Read<'s, Time>,
     );
 
@@ -28,223 +28,232 @@ impl<'s> System<'s> for SteeringSystem {
             transforms,
             mut steerings,
             tile_map,
-            mut history,
             time,
         ): Self::SystemData,
     ) {
+        let mut sounds = Vec::new();
         for (intent, transform, steering) in
             (&mut steering_intents, &transforms, &mut steerings).join()
         {
-            let old_pos = steering.pos;
-            let (anchored_x, anchored_y) = steering.to_anchor_coords(transform);
-            steering.pos = Pos::new(anchored_x.round() as i32, anchored_y.round() as i32);
+            let translation = Vector2::new(transform.translation().x, transform.translation().y);
+            steer(
+                steering,
+                intent,
+                &translation,
+                &tile_map,
+                time.fixed_seconds(),
+                &mut sounds,
+            );
+        }
+        sounds
+            .drain(..)
+            .for_each(|sound| sound_channel.single_write(SoundEvent::new(sound)));
+    }
+}
 
-            if steering.is_mid_air() {
-                steering.mode = steering.mode.add_to_duration(time.fixed_seconds());
-            }
+/// Adjusts the steering of a single entity, based on its intent and its current translation.
+/// This is where all the rules for walking, climbing, jumping and falling live.
+///
+/// Any sound effects that should be played as a result are pushed onto the given vector.
+/// This function does not touch the ECS, so it is shared by the SteeringSystem and the headless
+/// simulation.
+pub fn steer(
+    steering: &mut Steering,
+    intent: &mut SteeringIntent,
+    translation: &Vector2<f32>,
+    tile_map: &TileMap,
+    delta_seconds: f32,
+    sounds: &mut Vec<SoundType>,
+) {
+    let (anchored_x, anchored_y) = steering.translation_to_anchor_coords(translation);
+    steering.pos = Pos::new(anchored_x.round() as i32, anchored_y.round() as i32);
 
-            if steering.is_grounded() && !intent.face.is_neutral() {
-                steering.facing.x = intent.face;
-            }
+    if steering.is_mid_air() {
+        steering.mode = steering.mode.add_to_duration(delta_seconds);
+    }
 
-            // The following if-else construction checks if the steering mode should be changed.
-            let has_ground_beneath_feet = is_grounded(&steering, &tile_map);
-            if steering.is_falling()
-                && anchored_y <= steering.pos.y as f32
-                && has_ground_beneath_feet
-                && on_solid_ground(steering, &tile_map)
-            {
-                // If falling and you reached the floor, set to grounded.
-                steering.mode = SteeringMode::Grounded;
-                steering.destination = steering.pos;
-            } else if (steering.is_grounded()
-                && !has_ground_beneath_feet
-                && aligned_with_grid(steering.destination.x as f32, anchored_x, intent.walk))
-                || (steering.is_climbing() && intent.jump)
-            {
-                steering.mode = SteeringMode::Falling {
-                    x_movement: Direction1D::Neutral,
-                    starting_y_pos: transform.translation().y,
-                    duration: 0.,
-                };
-            } else if steering.is_grounded() && intent.jump {
-                if is_underneath_ceiling(steering, &tile_map) {
-                    sound_channel.single_write(SoundEvent::new(SoundType::CannotPerformAction));
-                } else {
-                    sound_channel.single_write(SoundEvent::new(SoundType::Jump));
-                    steering.mode = SteeringMode::Jumping {
-                        x_movement: intent.face,
-                        starting_y_pos: transform.translation().y,
-                        duration: 0.,
-                    };
-                }
-            } else if steering.jump_has_peaked() {
-                steering.mode = steering.mode.jump_to_fall();
-            } else if steering.is_grounded()
-                && aligned_with_grid(steering.destination.x as f32, anchored_x, intent.walk)
-                && ((intent.climb.is_positive() && can_climb_up(steering, &tile_map))
-                    || (intent.climb.is_negative() && can_climb_down(steering, &tile_map)))
-            {
-                steering.mode = SteeringMode::Climbing;
-                if !intent.walk.is_neutral() {
-                    intent.walk_invalidated = true;
-                }
-            } else if steering.is_climbing()
-                && aligned_with_grid(steering.destination.y as f32, anchored_y, intent.climb)
-                && !intent.walk_invalidated
-                && ((intent.walk.is_positive()
-                    && !is_against_wall_right(&steering, steering.pos.y as f32, &tile_map))
-                    || (intent.walk.is_negative()
-                        && !is_against_wall_left(&steering, steering.pos.y as f32, &tile_map)))
-            {
-                steering.mode = SteeringMode::Grounded;
-            }
+    if steering.is_grounded() && !intent.face.is_neutral() {
+        steering.facing.x = intent.face;
+    }
 
-            // This match will adjust the steering based on the current steering mode.
-            match steering.mode {
-                SteeringMode::Grounded => {
-                    if !intent.walk.is_neutral() {
-                        steering.facing = Direction2D::from(intent.walk, Direction1D::Neutral);
-                        let offset_from_destination = steering.destination.x as f32 - anchored_x;
-                        if offset_from_destination < f32::EPSILON && intent.walk.is_positive() {
-                            if !is_against_wall_right(&steering, steering.pos.y as f32, &tile_map) {
-                                steering.destination.x = steering.pos.x + 1;
-                                sound_channel.single_write(SoundEvent::new(SoundType::Step));
-                            }
-                        } else if offset_from_destination > -f32::EPSILON
-                            && intent.walk.is_negative()
-                        {
-                            if !is_against_wall_left(&steering, steering.pos.y as f32, &tile_map) {
-                                steering.destination.x = steering.pos.x - 1;
-                                sound_channel.single_write(SoundEvent::new(SoundType::Step));
-                            }
-                        } else if !intent
-                            .walk
-                            .aligns_with((steering.destination.x - steering.pos.x) as f32)
-                        {
-                            // TODO: Maybe remove, this doesn't seem to do anything.
-                            // Player wants to go back where they came from.
-                            steering.destination.x = steering.pos.x;
-                        }
+    // The following if-else construction checks if the steering mode should be changed.
+    let has_ground_beneath_feet = is_grounded(&steering, &tile_map);
+    if steering.is_falling()
+        && anchored_y <= steering.pos.y as f32
+        && has_ground_beneath_feet
+        && on_solid_ground(steering, &tile_map)
+    {
+        // If falling and you reached the floor, set to grounded.
+        steering.mode = SteeringMode::Grounded;
+        steering.destination = steering.pos;
+    } else if (steering.is_grounded()
+        && !has_ground_beneath_feet
+        && aligned_with_grid(steering.destination.x as f32, anchored_x, intent.walk))
+        || (steering.is_climbing() && intent.jump)
+    {
+        steering.mode = SteeringMode::Falling {
+            x_movement: Direction1D::Neutral,
+            starting_y_pos: translation.y,
+            duration: 0.,
+        };
+    } else if steering.is_grounded() && intent.jump {
+        if is_underneath_ceiling(steering, &tile_map) {
+            sounds.push(SoundType::CannotPerformAction);
+        } else {
+            sounds.push(SoundType::Jump);
+            steering.mode = SteeringMode::Jumping {
+                x_movement: intent.face,
+                starting_y_pos: translation.y,
+                duration: 0.,
+            };
+        }
+    } else if steering.jump_has_peaked() {
+        steering.mode = steering.mode.jump_to_fall();
+    } else if steering.is_grounded()
+        && aligned_with_grid(steering.destination.x as f32, anchored_x, intent.walk)
+        && ((intent.climb.is_positive() && can_climb_up(steering, &tile_map))
+            || (intent.climb.is_negative() && can_climb_down(steering, &tile_map)))
+    {
+        steering.mode = SteeringMode::Climbing;
+        if !intent.walk.is_neutral() {
+            intent.walk_invalidated = true;
+        }
+    } else if steering.is_climbing()
+        && aligned_with_grid(steering.destination.y as f32, anchored_y, intent.climb)
+        && !intent.walk_invalidated
+        && ((intent.walk.is_positive()
+            && !is_against_wall_right(&steering, steering.pos.y as f32, &tile_map))
+            || (intent.walk.is_negative()
+                && !is_against_wall_left(&steering, steering.pos.y as f32, &tile_map)))
+    {
+        steering.mode = SteeringMode::Grounded;
+    }
+
+    // This match will adjust the steering based on the current steering mode.
+    match steering.mode {
+        SteeringMode::Grounded => {
+            if !intent.walk.is_neutral() {
+                steering.facing = Direction2D::from(intent.walk, Direction1D::Neutral);
+                let offset_from_destination = steering.destination.x as f32 - anchored_x;
+                if offset_from_destination < f32::EPSILON && intent.walk.is_positive() {
+                    if !is_against_wall_right(&steering, steering.pos.y as f32, &tile_map) {
+                        steering.destination.x = steering.pos.x + 1;
+                        sounds.push(SoundType::Step);
                     }
-                }
-                SteeringMode::Climbing => {
-                    if !intent.climb.is_neutral() {
-                        steering.facing = Direction2D::from(Direction1D::Neutral, intent.climb);
-                        let offset_from_discrete_pos = steering.destination.y as f32 - anchored_y;
-                        if offset_from_discrete_pos < f32::EPSILON && intent.climb.is_positive() {
-                            if can_climb_up(steering, &tile_map) {
-                                sound_channel.single_write(SoundEvent::new(SoundType::LadderStep));
-                                steering.destination.y = steering.pos.y + 1;
-                            } else {
-                                steering.mode = SteeringMode::Grounded;
-                            }
-                        } else if offset_from_discrete_pos > -f32::EPSILON
-                            && intent.climb.is_negative()
-                        {
-                            if can_climb_down(steering, &tile_map) {
-                                sound_channel.single_write(SoundEvent::new(SoundType::LadderStep));
-                                steering.destination.y = steering.pos.y - 1;
-                            } else if above_air(steering, &tile_map) {
-                                steering.mode = SteeringMode::Falling {
-                                    x_movement: Direction1D::Neutral,
-                                    starting_y_pos: transform.translation().y,
-                                    duration: 0.,
-                                };
-                            } else {
-                                steering.mode = SteeringMode::Grounded;
-                            }
-                        } else if !intent
-                            .climb
-                            .aligns_with((steering.destination.y - steering.pos.y) as f32)
-                        {
-                            // TODO: Maybe remove, this doesn't seem to do anything.
-                            // Player wants to go back where they came from.
-                            steering.destination.y = steering.pos.y;
-                        }
+                } else if offset_from_destination > -f32::EPSILON && intent.walk.is_negative() {
+                    if !is_against_wall_left(&steering, steering.pos.y as f32, &tile_map) {
+                        steering.destination.x = steering.pos.x - 1;
+                        sounds.push(SoundType::Step);
                     }
+                } else if !intent
+                    .walk
+                    .aligns_with((steering.destination.x - steering.pos.x) as f32)
+                {
+                    // TODO: Maybe remove, this doesn't seem to do anything.
+                    // Player wants to go back where they came from.
+                    steering.destination.x = steering.pos.x;
                 }
-                SteeringMode::Falling {
-                    x_movement,
-                    starting_y_pos,
-                    duration,
-                } => {
-                    if x_movement.is_neutral() {
-                        // No horizontal movement.
-                        steering.destination.x = steering.pos.x;
-                    } else if x_movement.is_positive() {
-                        // Moving towards the right.
-                        if is_against_wall_right(steering, anchored_y, &tile_map) {
-                            steering.mode = SteeringMode::Falling {
-                                x_movement: Direction1D::Neutral,
-                                starting_y_pos,
-                                duration,
-                            };
-                        } else if aligned_with_grid(
-                            steering.destination.x as f32,
-                            anchored_x,
-                            x_movement,
-                        ) {
-                            steering.destination.x = steering.pos.x + 1;
-                        }
+            }
+        }
+        SteeringMode::Climbing => {
+            if !intent.climb.is_neutral() {
+                steering.facing = Direction2D::from(Direction1D::Neutral, intent.climb);
+                let offset_from_discrete_pos = steering.destination.y as f32 - anchored_y;
+                if offset_from_discrete_pos < f32::EPSILON && intent.climb.is_positive() {
+                    if can_climb_up(steering, &tile_map) {
+                        sounds.push(SoundType::LadderStep);
+                        steering.destination.y = steering.pos.y + 1;
                     } else {
-                        // Moving towards the left.
-                        if is_against_wall_left(steering, anchored_y, &tile_map) {
-                            steering.mode = SteeringMode::Falling {
-                                x_movement: Direction1D::Neutral,
-                                starting_y_pos,
-                                duration,
-                            };
-                        } else if aligned_with_grid(
-                            steering.destination.x as f32,
-                            anchored_x,
-                            x_movement,
-                        ) {
-                            steering.destination.x = steering.pos.x - 1;
-                        }
+                        steering.mode = SteeringMode::Grounded;
                     }
-                }
-                SteeringMode::Jumping {
-                    x_movement,
-                    starting_y_pos,
-                    duration,
-                } => {
-                    if !intent.jump_direction.is_neutral() {
-                        steering.mode = SteeringMode::Jumping {
-                            x_movement: intent.jump_direction,
-                            starting_y_pos,
-                            duration,
+                } else if offset_from_discrete_pos > -f32::EPSILON && intent.climb.is_negative() {
+                    if can_climb_down(steering, &tile_map) {
+                        sounds.push(SoundType::LadderStep);
+                        steering.destination.y = steering.pos.y - 1;
+                    } else if above_air(steering, &tile_map) {
+                        steering.mode = SteeringMode::Falling {
+                            x_movement: Direction1D::Neutral,
+                            starting_y_pos: translation.y,
+                            duration: 0.,
                         };
-                        steering.facing =
-                            Direction2D::from(intent.jump_direction, Direction1D::Neutral);
-                    }
-                    if x_movement.is_neutral() {
-                        // No horizontal movement.
-                        steering.destination.x = steering.pos.x;
-                    } else if x_movement.is_positive() {
-                        // Moving towards the right.
-                        if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement)
-                            && !is_against_wall_right(steering, steering.pos.y as f32, &tile_map)
-                        {
-                            steering.destination.x = steering.pos.x + 1;
-                        }
                     } else {
-                        // Moving towards the left.
-                        if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement)
-                            && !is_against_wall_left(steering, steering.pos.y as f32, &tile_map)
-                        {
-                            steering.destination.x = steering.pos.x - 1;
-                        }
+                        steering.mode = SteeringMode::Grounded;
                     }
+                } else if !intent
+                    .climb
+                    .aligns_with((steering.destination.y - steering.pos.y) as f32)
+                {
+                    // TODO: Maybe remove, this doesn't seem to do anything.
+                    // Player wants to go back where they came from.
+                    steering.destination.y = steering.pos.y;
                 }
-            };
-
-            // Push frame on history if player position changed.
-            if old_pos != steering.pos || history.force_key_frame {
-                history.push_frame(Frame::new(steering.pos));
             }
         }
-    }
+        SteeringMode::Falling {
+            x_movement,
+            starting_y_pos,
+            duration,
+        } => {
+            if x_movement.is_neutral() {
+                // No horizontal movement.
+                steering.destination.x = steering.pos.x;
+            } else if x_movement.is_positive() {
+                // Moving towards the right.
+                if is_against_wall_right(steering, anchored_y, &tile_map) {
+                    steering.mode = SteeringMode::Falling {
+                        x_movement: Direction1D::Neutral,
+                        starting_y_pos,
+                        duration,
+                    };
+                } else if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement) {
+                    steering.destination.x = steering.pos.x + 1;
+                }
+            } else {
+                // Moving towards the left.
+                if is_against_wall_left(steering, anchored_y, &tile_map) {
+                    steering.mode = SteeringMode::Falling {
+                        x_movement: Direction1D::Neutral,
+                        starting_y_pos,
+                        duration,
+                    };
+                } else if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement) {
+                    steering.destination.x = steering.pos.x - 1;
+                }
+            }
+        }
+        SteeringMode::Jumping {
+            x_movement,
+            starting_y_pos,
+            duration,
+        } => {
+            if !intent.jump_direction.is_neutral() {
+                steering.mode = SteeringMode::Jumping {
+                    x_movement: intent.jump_direction,
+                    starting_y_pos,
+                    duration,
+                };
+                steering.facing = Direction2D::from(intent.jump_direction, Direction1D::Neutral);
+            }
+            if x_movement.is_neutral() {
+                // No horizontal movement.
+                steering.destination.x = steering.pos.x;
+            } else if x_movement.is_positive() {
+                // Moving towards the right.
+                if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement)
+                    && !is_against_wall_right(steering, steering.pos.y as f32, &tile_map)
+                {
+                    steering.destination.x = steering.pos.x + 1;
+                }
+            } else {
+                // Moving towards the left.
+                if aligned_with_grid(steering.destination.x as f32, anchored_x, x_movement)
+                    && !is_against_wall_left(steering, steering.pos.y as f32, &tile_map)
+                {
+                    steering.destination.x = steering.pos.x - 1;
+                }
+            }
+        }
+    };
 }
 
 /// Returns true iff the player is aligned with the grid.
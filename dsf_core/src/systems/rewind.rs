@@ -1,6 +1,10 @@
 use crate::components::*;
+use crate::levels::restore_tile;
 use crate::resources::*;
+use crate::systems::add_equipped_sprite;
+use amethyst::core::ecs::{Entities, LazyUpdate};
 use amethyst::{
+    core::math::Vector2,
     core::timing::Time,
     core::transform::Transform,
     ecs::prelude::{Join, Read, ReadStorage, System, Write, WriteStorage},
@@ -18,14 +22,18 @@ impl<'s> System<'s> for RewindControlSystem {
         Read<'s, InputHandler<StringBindings>>,
         Read<'s, Time>,
         Read<'s, DebugSettings>,
+        Read<'s, ReplayMode>,
     );
 
     fn run(
         &mut self,
-        (mut current_state, mut rewind, mut history, input, time, config): Self::SystemData,
+        (mut current_state, mut rewind, mut history, input, time, config, replay_mode): Self::SystemData,
     ) {
         history.force_key_frame = false;
-        if input.action_is_down("shift").unwrap_or(false) {
+        // Rewinding is not recorded, so it would make recordings play out differently.
+        let wants_to_rewind =
+            !replay_mode.is_active() && input.action_is_down("shift").unwrap_or(false);
+        if wants_to_rewind {
             rewind.cooldown = match *current_state {
                 CurrentState::Running => config.seconds_per_rewind_frame,
                 CurrentState::Rewinding => {
@@ -46,6 +54,35 @@ impl<'s> System<'s> for RewindControlSystem {
     }
 }
 
+/// Pushes a new Frame onto the History whenever the game changes. Runs after all systems that
+/// change the game, so the Frame shows the game at the end of the tick.
+pub struct HistorySystem;
+
+impl<'s> System<'s> for HistorySystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Steering>,
+        ReadStorage<'s, Player>,
+        Read<'s, WinCondition>,
+        Write<'s, History>,
+    );
+
+    fn run(&mut self, (transforms, steerings, players, win, mut history): Self::SystemData) {
+        for (transform, steering, player) in (&transforms, &steerings, &players).join() {
+            if history.force_key_frame || history.has_changed(steering, player.equipped, &win.keys)
+            {
+                let translation =
+                    Vector2::new(transform.translation().x, transform.translation().y);
+                let frame = history.create_frame(translation, steering, player.equipped, &win.keys);
+                history.push_frame(frame);
+            }
+        }
+    }
+}
+
+/// Restores the game to an earlier Frame in the History. While rewinding, this steps back one
+/// Frame at a time. When the player undoes a move, this jumps back to the previous checkpoint.
 pub struct RewindSystem;
 
 impl<'s> System<'s> for RewindSystem {
@@ -53,29 +90,89 @@ impl<'s> System<'s> for RewindSystem {
     type SystemData = (
         WriteStorage<'s, Transform>,
         WriteStorage<'s, Steering>,
-        ReadStorage<'s, Player>,
-        Read<'s, Rewind>,
+        WriteStorage<'s, Player>,
+        ReadStorage<'s, EquippedTag>,
+        Read<'s, CurrentState>,
+        Write<'s, Rewind>,
         Write<'s, History>,
+        Write<'s, WinCondition>,
+        Write<'s, TileMap>,
+        Read<'s, LazyUpdate>,
+        Entities<'s>,
     );
 
     fn run(
         &mut self,
-        (mut transforms, mut steerings, player_tags, rewind, mut history): Self::SystemData,
+        (
+            mut transforms,
+            mut steerings,
+            mut players,
+            equipped_tags,
+            current_state,
+            mut rewind,
+            mut history,
+            mut win,
+            mut tile_map,
+            lazy,
+            entities,
+        ): Self::SystemData,
     ) {
-        if rewind.is_ready() {
-            if let Some(frame) = history.pop_frame() {
-                info!("Rewinding player to {:?}", frame);
-                for (_, transform, steering) in
-                    (&player_tags, &mut transforms, &mut steerings).join()
-                {
-                    let (centered_x, centered_y) =
-                        steering.to_centered_coords(frame.player_position);
-                    transform.set_translation_x(centered_x);
-                    transform.set_translation_y(centered_y);
-                    steering.pos = frame.player_position;
-                    steering.destination = frame.player_position;
+        let undo_requested = rewind.undo_requested;
+        rewind.undo_requested = false;
+        let frame = match *current_state {
+            CurrentState::Rewinding if rewind.is_ready() => history.pop_frame(),
+            CurrentState::Running if undo_requested => history.undo(),
+            _ => None,
+        };
+        if let Some(frame) = frame {
+            info!("Rewinding player to {:?}", frame.steering.pos);
+            for (player_entity, player, transform, steering) in
+                (&entities, &mut players, &mut transforms, &mut steerings).join()
+            {
+                // Undoing a move puts the player right back on the grid, instead of halfway
+                // through the step where they completed their previous move.
+                let (x, y) = if undo_requested && !frame.steering.is_mid_air() {
+                    frame.steering.to_centered_coords(frame.steering.pos)
+                } else {
+                    (frame.translation.x, frame.translation.y)
+                };
+                transform.set_translation_x(x);
+                transform.set_translation_y(y);
+                *steering = frame.steering.clone();
+                if player.equipped != frame.equipped {
+                    player.equipped = frame.equipped;
+                    for (_, entity) in (&equipped_tags, &entities).join() {
+                        entities
+                            .delete(entity)
+                            .expect("Failed to delete equipped tool sprite.");
+                    }
+                    if let Some((sprite, sprite_nr)) = frame
+                        .equipped
+                        .and_then(|tool_type| tool_sprite(&tile_map.tile_defs, tool_type))
+                    {
+                        lazy.exec_mut(move |world| {
+                            add_equipped_sprite(world, player_entity, &sprite, sprite_nr);
+                        });
+                    }
                 }
             }
+            win.keys = frame.keys.clone();
+            for (pos, tile_def_key) in history.restore_tiles(&frame) {
+                tile_map.restore_tile(pos, &tile_def_key);
+                lazy.exec_mut(move |world| restore_tile(world, &pos, &tile_def_key));
+            }
         }
     }
 }
+
+/// Finds the sprite of the given tool type in the tile definitions.
+fn tool_sprite(tile_defs: &TileDefinitions, tool_type: ToolType) -> Option<(SpriteType, usize)> {
+    tile_defs
+        .map
+        .values()
+        .filter(|tile_def| tile_def.archetype == Some(Archetype::Tool(tool_type)))
+        .find_map(|tile_def| match tile_def.asset {
+            Some(AssetType::Still(sprite, sprite_nr)) => Some((sprite, sprite_nr)),
+            _ => None,
+        })
+}
@@ -1,23 +1,23 @@
-use amethyst::core::ecs::{Entities, LazyUpdate};
+use amethyst::core::ecs::{Entities, Entity, LazyUpdate};
 use amethyst::{
     core::math::Vector2,
     core::transform::Transform,
     ecs::prelude::{Join, Read, ReadStorage, System, Write, WriteStorage},
-    input::{InputHandler, StringBindings},
 };
 
 use crate::components::*;
 use crate::levels::*;
 use crate::resources::*;
-use crate::systems::SoundEvent;
+use crate::sim::PlayerInput;
+use crate::systems::{touches, SoundEvent};
 use amethyst::core::ecs::shrev::EventChannel;
 use amethyst::core::Parent;
-use amethyst::prelude::{Builder, WorldExt};
+use amethyst::prelude::{Builder, World, WorldExt};
 
 /// Tool width and height, hardcoded for now.
 /// TODO: Don't hardcode.
-const TOOL_WIDTH: f32 = 2.;
-const TOOL_HEIGHT: f32 = 2.;
+pub const TOOL_WIDTH: f32 = 2.;
+pub const TOOL_HEIGHT: f32 = 2.;
 
 /// Checks if the player intersects any tools.
 /// If so, the tool will equipped by the player and will be removed from the game.
@@ -31,14 +31,26 @@ impl<'s> System<'s> for PickupSystem {
         WriteStorage<'s, Player>,
         ReadStorage<'s, Steering>,
         ReadStorage<'s, Tool>,
+        ReadStorage<'s, Block>,
         ReadStorage<'s, Transform>,
+        Write<'s, History>,
         Read<'s, LazyUpdate>,
         Entities<'s>,
     );
 
     fn run(
         &mut self,
-        (mut sound_channel, mut players, steerings, tools, transforms, lazy, entities): Self::SystemData,
+        (
+            mut sound_channel,
+            mut players,
+            steerings,
+            tools,
+            blocks,
+            transforms,
+            mut history,
+            lazy,
+            entities,
+        ): Self::SystemData,
     ) {
         let player = (&mut players, &entities, &steerings, &transforms)
             .join()
@@ -56,34 +68,29 @@ impl<'s> System<'s> for PickupSystem {
                 return;
             }
             // Find the first tool that intersects with the player:
-            let tool_opt = (&tools, &transforms, &entities)
-                .join()
-                .find(|(_, transform, _)| {
-                    let key_x = transform.translation().x;
-                    let key_y = transform.translation().y;
-                    pos.x - dimens.x / 2. < key_x + TOOL_WIDTH / 3.
-                        && pos.x + dimens.x / 2. > key_x - TOOL_WIDTH / 3.
-                        && pos.y - dimens.y / 2. < key_y + TOOL_HEIGHT / 3.
-                        && pos.y + dimens.y / 2. > key_y - TOOL_HEIGHT / 3.
-                });
-            if let Some((tool, _, tool_entity)) = tool_opt {
+            let tool_opt =
+                (&tools, &blocks, &transforms, &entities)
+                    .join()
+                    .find(|(_, _, transform, _)| {
+                        let tool_pos =
+                            Vector2::new(transform.translation().x, transform.translation().y);
+                        touches(
+                            &pos,
+                            &dimens,
+                            &tool_pos,
+                            &Vector2::new(TOOL_WIDTH, TOOL_HEIGHT),
+                        )
+                    });
+            if let Some((tool, block, _, tool_entity)) = tool_opt {
                 sound_channel.single_write(SoundEvent::new(SoundType::ToolPickup));
                 player.equipped = Some(tool.tool_type);
+                history.record_removed_tile(block.pos);
                 let (sprite, sprite_nr) = (tool.sprite, tool.sprite_nr);
                 lazy.exec_mut(move |world| {
                     world
                         .delete_entity(tool_entity)
                         .expect("Tried to delete tool, but failed.");
-                    let render = load_asset_from_world(&sprite, sprite_nr, world);
-                    world
-                        .create_entity()
-                        .with(EquippedTag)
-                        .with(Transform::default())
-                        .with(Parent {
-                            entity: player_entity,
-                        })
-                        .with(render)
-                        .build();
+                    add_equipped_sprite(world, player_entity, &sprite, sprite_nr);
                 });
             }
         }
@@ -101,8 +108,9 @@ impl<'s> System<'s> for UseToolSystem {
         ReadStorage<'s, Steering>,
         ReadStorage<'s, EquippedTag>,
         ReadStorage<'s, Block>,
-        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, PlayerInput>,
         Write<'s, TileMap>,
+        Write<'s, History>,
         Entities<'s>,
     );
 
@@ -116,49 +124,30 @@ impl<'s> System<'s> for UseToolSystem {
             blocks,
             input,
             mut tile_map,
+            mut history,
             entities,
         ): Self::SystemData,
     ) {
-        let wants_to_use_tool = input.action_is_down("jump").unwrap_or(false);
-        if !wants_to_use_tool {
+        if !input.jump {
             return;
         }
         for (player, steering) in (&mut players, &steerings).join() {
-            if !steering.is_grounded() {
-                return;
-            }
-            let targeted_blocks = match player.equipped {
-                Some(ToolType::BreakBlocksHorizontally(depth)) => {
-                    let player_is_not_too_far_away_from_wall =
-                        at_least_one_is_breakable(&tiles_to_side(1, steering), &tile_map);
-                    if player_is_not_too_far_away_from_wall {
-                        Some(tiles_to_side(depth, steering))
-                    } else {
-                        None
+            if let Some(targeted_blocks) = blocks_to_break(player, steering, &tile_map) {
+                sound_channel.single_write(SoundEvent::new(SoundType::Mining));
+                player.equipped = None;
+                targeted_blocks.iter().for_each(|pos| {
+                    if let Some(removed_pos) = tile_map.remove_tile(pos) {
+                        history.record_removed_tile(removed_pos);
                     }
+                });
+                for (_, entity) in (&equipped_tags, &entities).join() {
+                    entities
+                        .delete(entity)
+                        .expect("Failed to delete equipped tool sprite.");
                 }
-                Some(ToolType::BreakBlocksBelow(depth)) => Some(tiles_below(depth, steering)),
-                _ => None,
-            };
-            if let Some(targeted_blocks) = targeted_blocks {
-                let at_least_one_is_breakable =
-                    at_least_one_is_breakable(&targeted_blocks, &tile_map);
-                let none_are_unbreakable = none_are_unbreakable(&targeted_blocks, &tile_map);
-                if at_least_one_is_breakable && none_are_unbreakable {
-                    sound_channel.single_write(SoundEvent::new(SoundType::Mining));
-                    player.equipped = None;
-                    targeted_blocks.iter().for_each(|pos| {
-                        tile_map.remove_tile(pos);
-                    });
-                    for (_, entity) in (&equipped_tags, &entities).join() {
-                        entities
-                            .delete(entity)
-                            .expect("Failed to delete equipped tool sprite.");
-                    }
-                    for (block, entity) in (&blocks, &entities).join() {
-                        if targeted_blocks.contains(&block.pos) {
-                            entities.delete(entity).expect("Failed to delete block!");
-                        }
+                for (block, entity) in (&blocks, &entities).join() {
+                    if targeted_blocks.contains(&block.pos) {
+                        entities.delete(entity).expect("Failed to delete block!");
                     }
                 }
             }
@@ -166,6 +155,58 @@ impl<'s> System<'s> for UseToolSystem {
     }
 }
 
+/// Shows the given tool sprite on the player, to indicate they have that tool equipped.
+pub fn add_equipped_sprite(
+    world: &mut World,
+    player_entity: Entity,
+    sprite: &SpriteType,
+    sprite_nr: usize,
+) {
+    let render = load_asset_from_world(sprite, sprite_nr, world);
+    world
+        .create_entity()
+        .with(EquippedTag)
+        .with(Transform::default())
+        .with(Parent {
+            entity: player_entity,
+        })
+        .with(render)
+        .build();
+}
+
+/// Determines which blocks would be broken if the player used their equipped tool right now.
+/// Returns None if the player cannot use their tool at this time, either because they have no tool
+/// equipped, they are not standing on solid ground or because there is nothing to break.
+///
+/// This function does not touch the ECS, so it is shared by the UseToolSystem and the headless
+/// simulation.
+pub fn blocks_to_break(
+    player: &Player,
+    steering: &Steering,
+    tile_map: &TileMap,
+) -> Option<Vec<Pos>> {
+    if !steering.is_grounded() {
+        return None;
+    }
+    let targeted_blocks = match player.equipped {
+        Some(ToolType::BreakBlocksHorizontally(depth)) => {
+            let player_is_not_too_far_away_from_wall =
+                at_least_one_is_breakable(&tiles_to_side(1, steering), &tile_map);
+            if player_is_not_too_far_away_from_wall {
+                Some(tiles_to_side(depth, steering))
+            } else {
+                None
+            }
+        }
+        Some(ToolType::BreakBlocksBelow(depth)) => Some(tiles_below(depth, steering)),
+        _ => None,
+    };
+    targeted_blocks.filter(|targeted_blocks| {
+        at_least_one_is_breakable(&targeted_blocks, &tile_map)
+            && none_are_unbreakable(&targeted_blocks, &tile_map)
+    })
+}
+
 fn at_least_one_is_breakable(blocks: &[Pos], tile_map: &TileMap) -> bool {
     blocks.iter().any(|pos| {
         tile_map
@@ -12,10 +12,10 @@ use amethyst::core::ecs::shrev::EventChannel;
 
 /// Key width and height, hardcoded for now.
 /// TODO: Get rid of these hardcoded constants.
-const KEY_WIDTH: f32 = 2.;
-const KEY_HEIGHT: f32 = 2.;
-const DOOR_WIDTH: f32 = 4.;
-const DOOR_HEIGHT: f32 = 4.;
+pub const KEY_WIDTH: f32 = 2.;
+pub const KEY_HEIGHT: f32 = 2.;
+pub const DOOR_WIDTH: f32 = 4.;
+pub const DOOR_HEIGHT: f32 = 4.;
 
 /// Checks if the player intersects any keys.
 /// If so, the key will collected by the player and will be removed from the game.
@@ -32,6 +32,7 @@ impl<'s> System<'s> for KeyCollectionSystem {
         ReadStorage<'s, KeyDisplay>,
         ReadStorage<'s, Transform>,
         Write<'s, WinCondition>,
+        Write<'s, History>,
         Entities<'s>,
     );
 
@@ -45,6 +46,7 @@ impl<'s> System<'s> for KeyCollectionSystem {
             key_displays,
             transforms,
             mut win,
+            mut history,
             entities,
         ): Self::SystemData,
     ) {
@@ -61,18 +63,21 @@ impl<'s> System<'s> for KeyCollectionSystem {
             let collected_key = (&keys, &transforms, &entities)
                 .join()
                 .filter(|(_, transform, _)| {
-                    let key_x = transform.translation().x;
-                    let key_y = transform.translation().y;
-                    pos.x - dimens.x / 2. < key_x + KEY_WIDTH / 3.
-                        && pos.x + dimens.x / 2. > key_x - KEY_WIDTH / 3.
-                        && pos.y - dimens.y / 2. < key_y + KEY_HEIGHT / 3.
-                        && pos.y + dimens.y / 2. > key_y - KEY_HEIGHT / 3.
+                    let key_pos =
+                        Vector2::new(transform.translation().x, transform.translation().y);
+                    touches(
+                        &pos,
+                        &dimens,
+                        &key_pos,
+                        &Vector2::new(KEY_WIDTH, KEY_HEIGHT),
+                    )
                 })
                 .map(|(key, _, entity)| (key, entity))
                 .next();
             if let Some((key, key_entity)) = collected_key {
                 sound_channel.single_write(SoundEvent::new(SoundType::KeyPickup));
                 win.set_key_collected(&key.pos);
+                history.record_removed_tile(key.pos);
                 entities.delete(key_entity).expect("Failed to delete key.");
                 for (key_display, display_entity) in (&key_displays, &entities).join() {
                     if key_display.pos == key.pos {
@@ -121,13 +126,16 @@ impl<'s> System<'s> for WinSystem {
             .next();
         if let Some((pos, dimens)) = player_collider {
             for (_, door_transform) in (&doors, &transforms).join() {
-                let door_x = door_transform.translation().x;
-                let door_y = door_transform.translation().y;
-                if pos.x - dimens.x / 2. < door_x + DOOR_WIDTH / 3.
-                    && pos.x + dimens.x / 2. > door_x - DOOR_WIDTH / 3.
-                    && pos.y - dimens.y / 2. < door_y + DOOR_HEIGHT / 3.
-                    && pos.y + dimens.y / 2. > door_y - DOOR_HEIGHT / 3.
-                {
+                let door_pos = Vector2::new(
+                    door_transform.translation().x,
+                    door_transform.translation().y,
+                );
+                if touches(
+                    &pos,
+                    &dimens,
+                    &door_pos,
+                    &Vector2::new(DOOR_WIDTH, DOOR_HEIGHT),
+                ) {
                     sound_channel.single_write(SoundEvent::new(SoundType::Win));
                     win.reached_open_door = true;
                     lazy.exec_mut(move |world| {
@@ -138,3 +146,20 @@ impl<'s> System<'s> for WinSystem {
         }
     }
 }
+
+/// Returns true iff the player intersects with an object, such as a key, tool or door.
+/// The object's collider is a third of the given object dimensions in each direction, so the
+/// player has to actually move into the object before they interact with it.
+///
+/// All positions are the centers of their respective entities.
+pub fn touches(
+    player_pos: &Vector2<f32>,
+    player_dimens: &Vector2<f32>,
+    object_pos: &Vector2<f32>,
+    object_dimens: &Vector2<f32>,
+) -> bool {
+    player_pos.x - player_dimens.x / 2. < object_pos.x + object_dimens.x / 3.
+        && player_pos.x + player_dimens.x / 2. > object_pos.x - object_dimens.x / 3.
+        && player_pos.y - player_dimens.y / 2. < object_pos.y + object_dimens.y / 3.
+        && player_pos.y + player_dimens.y / 2. > object_pos.y - object_dimens.y / 3.
+}
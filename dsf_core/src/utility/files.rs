@@ -18,6 +18,11 @@ pub fn get_levels_dir() -> PathBuf {
     create_if_missing(get_world_dir().join("levels/"))
 }
 
+/// Blueprints are reusable structures of tiles that can be stamped into levels in the editor.
+pub fn get_blueprints_dir() -> PathBuf {
+    create_if_missing(get_world_dir().join("blueprints/"))
+}
+
 pub fn get_world_dir() -> PathBuf {
     get_assets_dir().join("world/")
 }
@@ -26,8 +31,17 @@ pub fn get_assets_dir() -> PathBuf {
     get_root_dir().join("assets/")
 }
 
+/// The directory that contains the assets. This is normally the application root, but when running
+/// one of the workspace's other binaries through cargo (such as dsf_checks), the application root is
+/// that crate's own directory. In that case, the assets are found in the workspace root above it.
 fn get_root_dir() -> PathBuf {
-    application_root_dir().expect("Root directory not found!")
+    let root = application_root_dir().expect("Root directory not found!");
+    match root.parent() {
+        Some(parent) if !root.join("assets/").is_dir() && parent.join("assets/").is_dir() => {
+            parent.to_path_buf()
+        }
+        _ => root,
+    }
 }
 
 fn create_if_missing(path: PathBuf) -> PathBuf {
@@ -52,6 +66,16 @@ pub fn get_user_cache_file() -> PathBuf {
     get_user_data_dir().join("cache.ron")
 }
 
+/// Holds the player's progress, see SaveGame.
+pub fn get_save_game_file() -> PathBuf {
+    get_user_data_dir().join("save_game.ron")
+}
+
 pub fn get_user_settings_dir() -> PathBuf {
     create_if_missing(get_user_data_dir().join("settings/"))
 }
+
+/// Recordings of play sessions, see Replay.
+pub fn get_replays_dir() -> PathBuf {
+    create_if_missing(get_user_data_dir().join("replays/"))
+}
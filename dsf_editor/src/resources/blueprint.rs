@@ -1,13 +1,13 @@
-use crate::resources::{EditorStatus, LevelEdit};
+use crate::resources::{BrushMode, EditorStatus, LevelEdit};
 use dsf_core::components::Pos;
-use dsf_core::resources::Tile;
+use dsf_core::resources::{Tile, TileDefinitions};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Contains a tile map. Is a blueprint for a structure of tiles inside a level.
 /// If you copy a selection in the level editor, that selection is stored as a Blueprint.
 /// Blueprints can be pasted. Blueprints can potentially be imported and exported from the editor.
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct Blueprint {
     pub dimensions: Pos,
     pub tiles: HashMap<Pos, Tile>,
@@ -21,13 +21,60 @@ impl Blueprint {
         }
     }
 
+    /// Returns the Blueprint that would be placed right now: the contents of the clipboard if the
+    /// editor is pasting, otherwise the tile on the brush repeated across the selection.
+    pub fn from_status(status: &EditorStatus, level_edit: &LevelEdit) -> Self {
+        match (&status.clipboard, status.pasting) {
+            (Some(clipboard), true) => clipboard.clone(),
+            _ => Blueprint::from_placing_tiles(status, level_edit),
+        }
+    }
+
+    /// Create a new instance of Blueprint by copying the tiles inside the current selection.
+    ///
+    /// Multi-tile tiles are copied in full as long as their anchor lies inside the selection. Tiles
+    /// whose anchor lies outside the selection are left out, because only part of them would fit.
+    /// Empty positions are copied as air blocks if the copy-air flag is enabled.
+    pub fn from_selection(status: &EditorStatus, level_edit: &LevelEdit) -> Self {
+        let lower_bounds = status.selection.lower_bounds();
+        let selection_dimens = status.selection.dimens();
+        let mut blueprint = Blueprint::new(selection_dimens);
+        for x in 0..selection_dimens.x {
+            for y in 0..selection_dimens.y {
+                let relative_pos = Pos::new(x, y);
+                match level_edit
+                    .tile_map
+                    .tiles
+                    .get(&(lower_bounds + relative_pos))
+                {
+                    Some(Tile::TileDefKey(key)) => {
+                        let dimens = level_edit.get_tile_def(key).dimens;
+                        blueprint.insert_tile(relative_pos, &dimens, Tile::TileDefKey(key.clone()));
+                    }
+                    None if status.copy_air => {
+                        blueprint.tiles.insert(relative_pos, Tile::AirBlock);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        blueprint
+    }
+
     /// Create a new instance of Blueprint, based on the current selection and the tile on the
-    /// brush. The blueprint will consist of rows and columns of whatever tile is on the brush,
-    /// starting at the lower-left corner of the selection.
+    /// brush. Which positions the brush tile is placed on depends on the brush mode:
+    ///
+    /// - Fill: rows and columns of the brush tile, starting at the lower-left corner of the
+    ///   selection.
+    /// - Outline: like Fill, but only the tiles that touch the edge of the selection.
+    /// - Line: a straight line from the start of the selection to the cursor.
+    /// - FloodFill: the contiguous region of identical tiles (or of air) under the cursor.
     ///
     /// When creating the Blueprint, existing tiles are ignored. It is therefore not guaranteed
     /// that all tiles in the blueprint will be placed; if force-place is not enabled and there are
-    /// tiles in the way, that will prevent the whole blueprint being placed.
+    /// tiles in the way, that will prevent the whole blueprint being placed. Flood fills are the
+    /// exception: they only cover the region they replace, and are always placed with force-place
+    /// (see `EditorStatus::forces_placement`).
     pub fn from_placing_tiles(status: &EditorStatus, level_edit: &LevelEdit) -> Self {
         let key = status.brush.get_key().as_ref();
         let tile_def = key.map(|key| level_edit.tile_map.tile_defs.get(key));
@@ -35,23 +82,167 @@ impl Blueprint {
             .map(|def| def.dimens)
             .unwrap_or_else(|| Pos::new(1, 1));
         let selection_dimens = (*status).selection.dimens();
+        let lower_bounds = status.selection.lower_bounds();
+        let relative = |pos: Pos| Pos::new(pos.x - lower_bounds.x, pos.y - lower_bounds.y);
+        let positions = match status.brush.mode() {
+            BrushMode::Fill => grid_positions(selection_dimens, brush_dimens),
+            BrushMode::Outline => grid_positions(selection_dimens, brush_dimens)
+                .into_iter()
+                .filter(|pos| {
+                    pos.x == 0
+                        || pos.y == 0
+                        || pos.x + brush_dimens.x >= selection_dimens.x
+                        || pos.y + brush_dimens.y >= selection_dimens.y
+                })
+                .collect(),
+            BrushMode::Line => line_positions(
+                relative(status.selection.start),
+                relative(status.selection.end),
+            ),
+            BrushMode::FloodFill => {
+                flood_fill_positions(status.selection.end, key, brush_dimens, level_edit)
+                    .into_iter()
+                    .map(relative)
+                    .collect()
+            }
+        };
         let mut blueprint = Blueprint::new(selection_dimens);
-        for x in (0..(selection_dimens.x)).step_by(brush_dimens.x as usize) {
-            for y in (0..(selection_dimens.y)).step_by(brush_dimens.y as usize) {
-                if let Some(key) = key {
-                    blueprint.insert_tile(
-                        Pos::new(x, y),
-                        &brush_dimens,
-                        Tile::TileDefKey(key.clone()),
-                    );
-                } else {
-                    blueprint.tiles.insert(Pos::new(x, y), Tile::AirBlock);
+        for pos in positions {
+            if let Some(key) = key {
+                // Lines and flood fills can produce positions that are closer together than
+                // the brush is wide. Skip those rather than placing overlapping tiles.
+                if !blueprint.overlaps(pos, brush_dimens) {
+                    blueprint.insert_tile(pos, &brush_dimens, Tile::TileDefKey(key.clone()));
                 }
+            } else {
+                blueprint.tiles.insert(pos, Tile::AirBlock);
             }
         }
         blueprint
     }
 
+    /// Returns a copy of this blueprint, mirrored along the vertical axis, and the keys of the
+    /// tiles that had to be left out. See `transformed`.
+    pub fn flipped_horizontally(&self, tile_defs: &TileDefinitions) -> (Self, Vec<String>) {
+        let width = self.dimensions.x;
+        self.transformed(self.dimensions, tile_defs, |pos| {
+            Pos::new(width - 1 - pos.x, pos.y)
+        })
+    }
+
+    /// Returns a copy of this blueprint, mirrored along the horizontal axis, and the keys of the
+    /// tiles that had to be left out. See `transformed`.
+    pub fn flipped_vertically(&self, tile_defs: &TileDefinitions) -> (Self, Vec<String>) {
+        let height = self.dimensions.y;
+        self.transformed(self.dimensions, tile_defs, |pos| {
+            Pos::new(pos.x, height - 1 - pos.y)
+        })
+    }
+
+    /// Returns a copy of this blueprint, rotated 90 degrees clockwise, and the keys of the tiles
+    /// that had to be left out. See `transformed`.
+    pub fn rotated_clockwise(&self, tile_defs: &TileDefinitions) -> (Self, Vec<String>) {
+        let width = self.dimensions.x;
+        let new_dimensions = Pos::new(self.dimensions.y, self.dimensions.x);
+        self.transformed(new_dimensions, tile_defs, |pos| {
+            Pos::new(pos.y, width - 1 - pos.x)
+        })
+    }
+
+    /// Creates a new blueprint by moving every cell of this blueprint to the position given by
+    /// `map_cell`, which must map the blueprint onto a rectangle of the given dimensions.
+    ///
+    /// Tiles are re-anchored: the footprint of every tile is mapped, and the tile is anchored at
+    /// the bottom-left of its new footprint. Tiles themselves cannot be rotated, so when a tile
+    /// that is not square is rotated, its footprint no longer matches its dimensions. The tile
+    /// keeps its own dimensions and is centered on its rotated footprint instead (rounded down).
+    /// That can make it stick out of the rotated rectangle, in which case the blueprint grows to
+    /// fit it, and it can make it overlap another tile. Tiles that would overlap are left out;
+    /// their keys are returned alongside the blueprint, so the designer can be told about them.
+    ///
+    /// Air blocks are mapped like any other cell. Dummy entries are recreated from the tile
+    /// definitions rather than mapped.
+    fn transformed<F>(
+        &self,
+        new_dimensions: Pos,
+        tile_defs: &TileDefinitions,
+        map_cell: F,
+    ) -> (Self, Vec<String>)
+    where
+        F: Fn(Pos) -> Pos,
+    {
+        // Positions in here may be negative or lie beyond the new dimensions. The blueprint is
+        // shifted back into place at the end.
+        let mut mapped = Blueprint::new(new_dimensions);
+        let mut dropped = Vec::new();
+        // Sort the anchors, so the outcome does not depend on the order of the HashMap.
+        let mut anchors = self
+            .tiles
+            .iter()
+            .filter_map(|(pos, tile)| match tile {
+                Tile::TileDefKey(key) => Some((*pos, key)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        anchors.sort_by_key(|(pos, _)| (pos.y, pos.x));
+        for (pos, key) in anchors {
+            let dimens = tile_defs.get(key).dimens;
+            let first_corner = map_cell(pos);
+            let second_corner = map_cell(pos.append_xy(dimens.x - 1, dimens.y - 1));
+            let footprint_pos = Pos::new(
+                first_corner.x.min(second_corner.x),
+                first_corner.y.min(second_corner.y),
+            );
+            let footprint_dimens = Pos::new(
+                (first_corner.x - second_corner.x).abs() + 1,
+                (first_corner.y - second_corner.y).abs() + 1,
+            );
+            let new_pos = footprint_pos.append_xy(
+                (footprint_dimens.x - dimens.x).div_euclid(2),
+                (footprint_dimens.y - dimens.y).div_euclid(2),
+            );
+            if mapped.overlaps(new_pos, dimens) {
+                dropped.push(key.clone());
+            } else {
+                mapped.insert_tile(new_pos, &dimens, Tile::TileDefKey(key.clone()));
+            }
+        }
+        self.tiles
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::AirBlock))
+            .for_each(|(pos, _)| {
+                mapped.tiles.entry(map_cell(*pos)).or_insert(Tile::AirBlock);
+            });
+        (mapped.shifted_into_place(), dropped)
+    }
+
+    /// Moves all tiles so that none of them lie below or to the left of the origin, and grows
+    /// the dimensions so that all tiles fit inside them.
+    fn shifted_into_place(self) -> Self {
+        let lower = self.tiles.keys().fold(Pos::default(), |lower, pos| {
+            Pos::new(lower.x.min(pos.x), lower.y.min(pos.y))
+        });
+        let upper = self.tiles.keys().fold(self.dimensions, |upper, pos| {
+            Pos::new(upper.x.max(pos.x + 1), upper.y.max(pos.y + 1))
+        });
+        let offset = Pos::new(-lower.x, -lower.y);
+        let tiles = self
+            .tiles
+            .into_iter()
+            .map(|(pos, tile)| {
+                let tile = match tile {
+                    Tile::Dummy(anchor) => Tile::Dummy(anchor + offset),
+                    tile => tile,
+                };
+                (pos + offset, tile)
+            })
+            .collect();
+        Blueprint {
+            dimensions: Pos::new(upper.x - lower.x, upper.y - lower.y),
+            tiles,
+        }
+    }
+
     fn insert_tile(&mut self, pos: Pos, dimens: &Pos, tile: Tile) {
         self.tiles.insert(pos, tile);
         for x in pos.x..(pos.x + dimens.x) {
@@ -70,3 +261,172 @@ impl Blueprint {
             .any(|x| (pos.y..(pos.y + dimens.y)).any(|y| self.tiles.get(&Pos::new(x, y)).is_some()))
     }
 }
+
+/// Returns the positions of the lower-left corners of a grid of tiles with the given dimensions
+/// that fills a rectangle starting at the origin.
+fn grid_positions(area: Pos, tile_dimens: Pos) -> Vec<Pos> {
+    (0..area.x)
+        .step_by(tile_dimens.x as usize)
+        .flat_map(|x| {
+            (0..area.y)
+                .step_by(tile_dimens.y as usize)
+                .map(move |y| Pos::new(x, y))
+        })
+        .collect()
+}
+
+/// Returns all positions on a straight line between the two given positions, inclusive.
+/// Uses Bresenham's line algorithm.
+fn line_positions(from: Pos, to: Pos) -> Vec<Pos> {
+    let delta_x = (to.x - from.x).abs();
+    let delta_y = -(to.y - from.y).abs();
+    let step_x = (to.x - from.x).signum();
+    let step_y = (to.y - from.y).signum();
+    let mut error = delta_x + delta_y;
+    let mut pos = from;
+    let mut positions = vec![pos];
+    while pos != to {
+        let double_error = 2 * error;
+        if double_error >= delta_y {
+            error += delta_y;
+            pos.x += step_x;
+        }
+        if double_error <= delta_x {
+            error += delta_x;
+            pos.y += step_y;
+        }
+        positions.push(pos);
+    }
+    positions
+}
+
+/// Returns the positions (in world coordinates) where brush tiles should be placed to flood fill
+/// the region around the given start position. The region consists of all positions that are
+/// connected to the start position, horizontally or vertically, and are covered by the same kind
+/// of tile (or are all air). It never extends beyond the world bounds.
+///
+/// Only positions where the entire brush tile fits inside the region are returned. They are
+/// sorted bottom to top, left to right. Returns nothing if the region already consists of the
+/// tile on the brush.
+fn flood_fill_positions(
+    start: Pos,
+    brush_key: Option<&String>,
+    brush_dimens: Pos,
+    level_edit: &LevelEdit,
+) -> Vec<Pos> {
+    let target = level_edit.tile_key_at(&start);
+    if target == brush_key.map(|key| key.as_str()) {
+        return vec![];
+    }
+    let single = Pos::new(1, 1);
+    let mut region = HashSet::new();
+    let mut queue = vec![start];
+    while let Some(pos) = queue.pop() {
+        if region.contains(&pos)
+            || !level_edit.bounds().encloses(&pos, &single)
+            || level_edit.tile_key_at(&pos) != target
+        {
+            continue;
+        }
+        region.insert(pos);
+        queue.extend(
+            [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .map(|(x, y)| pos.append_xy(*x, *y)),
+        );
+    }
+    let mut positions = region
+        .iter()
+        .filter(|pos| {
+            (0..brush_dimens.x)
+                .all(|x| (0..brush_dimens.y).all(|y| region.contains(&pos.append_xy(x, y))))
+        })
+        .copied()
+        .collect::<Vec<Pos>>();
+    positions.sort_by_key(|pos| (pos.y, pos.x));
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsf_core::levels::load_tile_definitions;
+
+    fn blueprint(dimensions: Pos, tiles: &[(Pos, &str)], tile_defs: &TileDefinitions) -> Blueprint {
+        let mut blueprint = Blueprint::new(dimensions);
+        for (pos, key) in tiles {
+            let dimens = tile_defs.get(key).dimens;
+            blueprint.insert_tile(*pos, &dimens, Tile::TileDefKey(key.to_string()));
+        }
+        blueprint
+    }
+
+    fn anchors(blueprint: &Blueprint) -> Vec<(Pos, String)> {
+        let mut anchors = blueprint
+            .tiles
+            .iter()
+            .filter_map(|(pos, tile)| match tile {
+                Tile::TileDefKey(key) => Some((*pos, key.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        anchors.sort();
+        anchors
+    }
+
+    #[test]
+    fn flipping_re_anchors_multi_tile_entries() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let original = blueprint(
+            Pos::new(4, 2),
+            &[(Pos::new(0, 0), "Key"), (Pos::new(3, 1), "Block1")],
+            &tile_defs,
+        );
+        let (flipped, dropped) = original.flipped_horizontally(&tile_defs);
+        assert!(dropped.is_empty());
+        assert_eq!(flipped.dimensions, Pos::new(4, 2));
+        assert_eq!(
+            anchors(&flipped),
+            vec![
+                (Pos::new(0, 1), "Block1".to_string()),
+                (Pos::new(2, 0), "Key".to_string())
+            ]
+        );
+        assert!(matches!(
+            flipped.tiles[&Pos::new(3, 1)],
+            Tile::Dummy(anchor) if anchor == Pos::new(2, 0)
+        ));
+        let (back, _) = flipped.flipped_horizontally(&tile_defs);
+        assert_eq!(anchors(&back), anchors(&original));
+    }
+
+    #[test]
+    fn rotating_grows_the_blueprint_to_fit_non_square_tiles() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let original = blueprint(Pos::new(2, 1), &[(Pos::new(0, 0), "Ladder")], &tile_defs);
+        let (rotated, dropped) = original.rotated_clockwise(&tile_defs);
+        assert!(dropped.is_empty());
+        assert_eq!(rotated.dimensions, Pos::new(2, 2));
+        assert_eq!(
+            anchors(&rotated),
+            vec![(Pos::new(0, 0), "Ladder".to_string())]
+        );
+        assert!(rotated
+            .tiles
+            .keys()
+            .all(|pos| pos.x >= 0 && pos.y >= 0 && pos.x < 2 && pos.y < 2));
+    }
+
+    #[test]
+    fn rotating_reports_tiles_that_no_longer_fit() {
+        let tile_defs = load_tile_definitions().expect("Failed to load tile definitions.");
+        let original = blueprint(
+            Pos::new(2, 2),
+            &[(Pos::new(0, 0), "Ladder"), (Pos::new(0, 1), "Ladder")],
+            &tile_defs,
+        );
+        let (rotated, dropped) = original.rotated_clockwise(&tile_defs);
+        assert_eq!(dropped, vec!["Ladder".to_string()]);
+        assert_eq!(anchors(&rotated).len(), 1);
+    }
+}
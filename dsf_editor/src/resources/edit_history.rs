@@ -0,0 +1,97 @@
+use dsf_core::components::Pos;
+use dsf_core::resources::WorldBounds;
+
+/// The maximum number of steps that can be undone. Once the history grows beyond this, the oldest
+/// steps are forgotten.
+const MAX_UNDO_STEPS: usize = 200;
+
+/// A single, reversible change to the level.
+#[derive(Debug, Clone)]
+pub enum EditOp {
+    /// A tile with the given key was placed with its anchor at the given position.
+    AddTile { pos: Pos, tile_def_key: String },
+    /// The tile with the given key that was anchored at the given position was removed.
+    RemoveTile { pos: Pos, tile_def_key: String },
+    /// The world bounds were changed.
+    ChangeBounds {
+        before: WorldBounds,
+        after: WorldBounds,
+    },
+}
+
+impl EditOp {
+    /// Returns the operation that reverts this operation.
+    pub fn inverse(&self) -> EditOp {
+        match self {
+            EditOp::AddTile { pos, tile_def_key } => EditOp::RemoveTile {
+                pos: *pos,
+                tile_def_key: tile_def_key.clone(),
+            },
+            EditOp::RemoveTile { pos, tile_def_key } => EditOp::AddTile {
+                pos: *pos,
+                tile_def_key: tile_def_key.clone(),
+            },
+            EditOp::ChangeBounds { before, after } => EditOp::ChangeBounds {
+                before: after.clone(),
+                after: before.clone(),
+            },
+        }
+    }
+}
+
+/// One undoable step in the editor. Consists of all operations that were performed as the result
+/// of a single user action, in the order in which they were performed.
+pub type EditStep = Vec<EditOp>;
+
+/// Keeps track of the changes made to the level in the editor, so they can be undone and redone.
+///
+/// Operations are recorded into a pending step. Call `commit` once a user action is complete to
+/// turn all pending operations into a single undoable step.
+#[derive(Debug, Default, Clone)]
+pub struct EditHistory {
+    undo_stack: Vec<EditStep>,
+    redo_stack: Vec<EditStep>,
+    pending: EditStep,
+}
+
+impl EditHistory {
+    /// Record an operation as part of the pending step.
+    pub(crate) fn record(&mut self, op: EditOp) {
+        self.pending.push(op);
+    }
+
+    /// Finish the pending step and push it onto the undo stack.
+    /// Making a new change invalidates everything that could be redone.
+    pub(crate) fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.undo_stack.push(std::mem::take(&mut self.pending));
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Take the most recent step off the undo stack. It is the caller's responsibility to revert
+    /// it and to hand it back through `push_redo`.
+    pub(crate) fn pop_undo(&mut self) -> Option<EditStep> {
+        self.commit();
+        self.undo_stack.pop()
+    }
+
+    /// Take the most recently undone step off the redo stack. It is the caller's responsibility
+    /// to reapply it and to hand it back through `push_undo`.
+    pub(crate) fn pop_redo(&mut self) -> Option<EditStep> {
+        self.commit();
+        self.redo_stack.pop()
+    }
+
+    pub(crate) fn push_undo(&mut self, step: EditStep) {
+        self.undo_stack.push(step);
+    }
+
+    pub(crate) fn push_redo(&mut self, step: EditStep) {
+        self.redo_stack.push(step);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::resources::{EditHistory, EditOp};
 use dsf_core::components::Pos;
-use dsf_core::levels::LevelSave;
+use dsf_core::levels::{LevelMetadata, LevelSave, CURRENT_LEVEL_VERSION};
 use dsf_core::resources::{Tile, TileDefinition, TileDefinitions, TileMap, WorldBounds};
 use std::collections::{HashMap, HashSet};
 
@@ -10,6 +11,10 @@ pub struct LevelEdit {
     /// A list of tile positions that are marked are dirty and must be redrawn.
     /// Whenever you add, update or remove a tile in the editor, you must mark it as dirty.
     pub dirty: HashSet<Pos>,
+    /// All changes made to the level so far, used to undo and redo them.
+    pub history: EditHistory,
+    /// Title, author and other information about the level. Is saved along with the tiles.
+    pub metadata: LevelMetadata,
 }
 
 /// Implements the standard converter from LevelEdit to LevelSave. In other words: convert a level
@@ -26,6 +31,8 @@ impl From<LevelEdit> for LevelSave {
             }
         });
         LevelSave {
+            version: CURRENT_LEVEL_VERSION,
+            metadata: item.metadata,
             world_bounds: item.tile_map.world_bounds,
             tiles: map,
         }
@@ -33,11 +40,14 @@ impl From<LevelEdit> for LevelSave {
 }
 
 impl LevelEdit {
-    pub(crate) fn new(level_save: LevelSave, tile_defs: TileDefinitions) -> Self {
+    pub(crate) fn new(mut level_save: LevelSave, tile_defs: TileDefinitions) -> Self {
         let initial_dirty = level_save.tiles.keys().copied().collect::<HashSet<Pos>>();
+        let metadata = std::mem::take(&mut level_save.metadata);
         LevelEdit {
             tile_map: TileMap::for_editing(level_save, tile_defs),
             dirty: initial_dirty,
+            history: EditHistory::default(),
+            metadata,
         }
     }
 
@@ -51,20 +61,92 @@ impl LevelEdit {
     }
 
     /// Attempt to place the given tile at the given position.
+    /// The changes are recorded in the edit history, but are not committed as a separate step.
     pub(crate) fn place_tile(&mut self, force_place: bool, pos: Pos, tile: Option<Tile>) {
         let mut dry_run = self.check_place_tile(force_place, pos, tile);
         dry_run.to_be_removed.iter().for_each(|delete_pos| {
-            if let Some(removed_pos) = self.tile_map.remove_tile(&delete_pos) {
-                self.dirty.insert(removed_pos);
+            if let Some(Tile::TileDefKey(key)) = self.tile_map.tiles.get(delete_pos) {
+                let op = EditOp::RemoveTile {
+                    pos: *delete_pos,
+                    tile_def_key: key.clone(),
+                };
+                self.apply(&op);
+                self.history.record(op);
             }
         });
-        dry_run
-            .to_be_added
-            .drain(..)
-            .for_each(|(pos, key, dimensions)| {
-                self.tile_map.put_tile(pos, key, &dimensions);
-                self.dirty.insert(pos);
-            });
+        dry_run.to_be_added.drain(..).for_each(|(pos, key, _)| {
+            let op = EditOp::AddTile {
+                pos,
+                tile_def_key: key,
+            };
+            self.apply(&op);
+            self.history.record(op);
+        });
+    }
+
+    /// Move the borders of the world bounds that the given position is on in the given
+    /// directions. The change is recorded in the edit history.
+    pub(crate) fn adjust_bounds(&mut self, pos: Pos, delta_x: i32, delta_y: i32) {
+        let before = self.bounds().clone();
+        let mut after = before.clone();
+        after.adjust_x(pos.x, delta_x);
+        after.adjust_y(pos.y, delta_y);
+        if before != after {
+            let op = EditOp::ChangeBounds { before, after };
+            self.apply(&op);
+            self.history.record(op);
+        }
+    }
+
+    /// Finish the current user action: all changes made since the previous commit become a single
+    /// step that can be undone.
+    pub(crate) fn commit_edit(&mut self) {
+        self.history.commit();
+    }
+
+    /// Revert the most recent step in the edit history.
+    /// Returns true if there was anything to undo.
+    pub(crate) fn undo(&mut self) -> bool {
+        if let Some(step) = self.history.pop_undo() {
+            step.iter().rev().for_each(|op| self.apply(&op.inverse()));
+            self.history.push_redo(step);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reapply the most recently undone step.
+    /// Returns true if there was anything to redo.
+    pub(crate) fn redo(&mut self) -> bool {
+        if let Some(step) = self.history.pop_redo() {
+            step.iter().for_each(|op| self.apply(op));
+            self.history.push_undo(step);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Perform a single operation on the level and mark the affected tiles as dirty.
+    /// Does not record anything in the edit history.
+    fn apply(&mut self, op: &EditOp) {
+        match op {
+            EditOp::AddTile { pos, tile_def_key } => {
+                let dimensions = self.get_tile_def(tile_def_key).dimens;
+                self.tile_map
+                    .put_tile(*pos, tile_def_key.clone(), &dimensions);
+                self.dirty.insert(*pos);
+            }
+            EditOp::RemoveTile { pos, .. } => {
+                if let Some(removed_pos) = self.tile_map.remove_tile(pos) {
+                    self.dirty.insert(removed_pos);
+                }
+            }
+            EditOp::ChangeBounds { after, .. } => {
+                self.tile_map.world_bounds = after.clone();
+            }
+        }
     }
 
     /// Does a dry-run to check what would happen if we'd place the given tile right now.
@@ -128,15 +210,21 @@ impl LevelEdit {
         self.tile_map.tile_defs.get(tile_def_key)
     }
 
+    /// Returns the key of the tile that covers the given position, or None if it is air.
+    /// Dummy tiles are resolved to the key stored at their anchor.
+    pub(crate) fn tile_key_at(&self, pos: &Pos) -> Option<&str> {
+        self.tile_map.get_actual_pos(pos).and_then(|anchor| {
+            match self.tile_map.tiles.get(&anchor) {
+                Some(Tile::TileDefKey(key)) => Some(key.as_str()),
+                _ => None,
+            }
+        })
+    }
+
     /// Returns the world bounds for this level.
     pub(crate) fn bounds(&self) -> &WorldBounds {
         &self.tile_map.world_bounds
     }
-
-    /// Returns a mutable reference to the world bounds for this level.
-    pub(crate) fn bounds_mut(&mut self) -> &mut WorldBounds {
-        &mut self.tile_map.world_bounds
-    }
 }
 
 /// When performing a place-tile dry-run to determine what tiles (if any) to place and what tiles
@@ -1,6 +1,7 @@
 mod blueprint;
 mod config;
 mod debug_lines;
+mod edit_history;
 mod level_edit;
 mod status;
 mod tile_edit;
@@ -8,6 +9,7 @@ mod tile_edit;
 pub use self::blueprint::*;
 pub use self::config::*;
 pub use self::debug_lines::*;
+pub use self::edit_history::*;
 pub use self::level_edit::*;
 pub use self::status::*;
 pub use self::tile_edit::*;
@@ -1,19 +1,95 @@
-use dsf_core::resources::TileDefinitions;
+use dsf_core::resources::{TileCategory, TileDefinitions};
+
+/// Determines which positions the tile on the brush is placed on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BrushMode {
+    /// Fill the whole selection.
+    Fill,
+    /// Only place tiles along the edges of the selection, leaving the inside untouched.
+    Outline,
+    /// Place tiles along a straight line from the start of the selection to the cursor.
+    Line,
+    /// Replace the contiguous region of identical tiles (or of air) under the cursor.
+    /// The region is bounded by the world bounds, not by the selection.
+    FloodFill,
+}
+
+impl Default for BrushMode {
+    fn default() -> Self {
+        BrushMode::Fill
+    }
+}
+
+impl BrushMode {
+    fn next(self) -> Self {
+        match self {
+            BrushMode::Fill => BrushMode::Outline,
+            BrushMode::Outline => BrushMode::Line,
+            BrushMode::Line => BrushMode::FloodFill,
+            BrushMode::FloodFill => BrushMode::Fill,
+        }
+    }
+}
+
+/// The number of hotbar slots. Slots are selected with the number keys 1 through 9.
+pub const HOTBAR_SLOTS: usize = 9;
+
+/// How many recently used tiles are remembered.
+pub const RECENT_CAPACITY: usize = 5;
+
+/// A single tile in the palette.
+#[derive(Debug, Clone)]
+pub struct PaletteEntry {
+    /// The key of the tile definition, or None for air.
+    pub key: Option<String>,
+    /// The group this entry is listed under. Air is listed with the terrain.
+    pub category: TileCategory,
+}
+
+impl PaletteEntry {
+    /// The name shown to the user.
+    pub fn label(&self) -> &str {
+        self.key.as_deref().unwrap_or("Air")
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Brush {
-    palette: Vec<Option<String>>,
+    /// All tiles that can be placed, grouped by category.
+    palette: Vec<PaletteEntry>,
     palette_index: usize,
+    mode: BrushMode,
+    /// The tiles that were placed most recently, most recent first. Does not contain duplicates.
+    recent: Vec<Option<String>>,
+    /// The tile in each hotbar slot.
+    hotbar: Vec<Option<String>>,
 }
 
 impl Brush {
+    /// Fill the palette with all tile definitions, sorted by category and then by key.
+    /// The hotbar starts out with the first entries of the palette. It keeps its contents if the
+    /// palette is set again later.
     pub fn set_palette(&mut self, defs: &TileDefinitions) {
         self.palette.clear();
-        self.palette.push(None);
-        defs.map.keys().for_each(|key| {
-            self.palette.push(Some(key.clone()));
+        self.palette.push(PaletteEntry {
+            key: None,
+            category: TileCategory::Terrain,
         });
-        self.palette.sort();
+        let mut entries = defs
+            .map
+            .iter()
+            .map(|(key, def)| PaletteEntry {
+                key: Some(key.clone()),
+                category: def.category,
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| (a.category, &a.key).cmp(&(b.category, &b.key)));
+        self.palette.extend(entries);
+        if self.hotbar.is_empty() {
+            self.hotbar = (0..HOTBAR_SLOTS)
+                .map(|slot| self.palette.get(slot).and_then(|entry| entry.key.clone()))
+                .collect();
+        }
     }
     pub fn select_previous(&mut self) -> Option<String> {
         self.select(-1)
@@ -30,9 +106,101 @@ impl Brush {
         new_key.clone()
     }
 
-    pub fn get_key(&self) -> &Option<String> {
+    /// Select the palette entry with the given index. Returns false if there is no such entry.
+    pub fn select_index(&mut self, index: usize) -> bool {
+        if index < self.palette.len() {
+            self.palette_index = index;
+            info!("Selected brush: {:?}", self.get_key());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Select the given tile. Returns false if it is not in the palette.
+    pub fn select_key(&mut self, key: &Option<String>) -> bool {
+        self.palette
+            .iter()
+            .position(|entry| &entry.key == key)
+            .map(|index| self.select_index(index))
+            .unwrap_or(false)
+    }
+
+    /// Select the tile in the given hotbar slot. Slots are numbered from zero.
+    pub fn select_hotbar(&mut self, slot: usize) -> bool {
+        match self.hotbar.get(slot).cloned() {
+            Some(key) => self.select_key(&key),
+            None => false,
+        }
+    }
+
+    /// Put the currently selected tile in the given hotbar slot. Slots are numbered from zero.
+    pub fn assign_hotbar(&mut self, slot: usize) {
+        let key = self.get_key().clone();
+        if let Some(hotbar_slot) = self.hotbar.get_mut(slot) {
+            info!("Assigned brush {:?} to hotbar slot {}", key, slot + 1);
+            *hotbar_slot = key;
+        }
+    }
+
+    /// Remember that the currently selected tile was just used.
+    pub fn mark_used(&mut self) {
+        let key = self.get_key().clone();
+        self.recent.retain(|recent_key| recent_key != &key);
+        self.recent.insert(0, key);
+        self.recent.truncate(RECENT_CAPACITY);
+    }
+
+    /// Switch back to the most recently used tile that is not currently selected.
+    /// Returns false if there is no such tile.
+    pub fn select_last_used(&mut self) -> bool {
+        let current = self.get_key().clone();
+        match self.recent.iter().find(|key| **key != current).cloned() {
+            Some(key) => self.select_key(&key),
+            None => false,
+        }
+    }
+
+    /// Returns the first palette entry whose name contains the query, ignoring case.
+    /// Returns None if no entry matches.
+    pub fn find(&self, query: &str) -> Option<usize> {
+        let query = query.to_lowercase();
         self.palette
+            .iter()
+            .position(|entry| entry.label().to_lowercase().contains(&query))
+    }
+
+    pub fn select_next_mode(&mut self) -> BrushMode {
+        self.mode = self.mode.next();
+        info!("Selected brush mode: {:?}", self.mode);
+        self.mode
+    }
+
+    pub fn mode(&self) -> BrushMode {
+        self.mode
+    }
+
+    pub fn palette(&self) -> &[PaletteEntry] {
+        &self.palette
+    }
+
+    pub fn palette_index(&self) -> usize {
+        self.palette_index
+    }
+
+    pub fn recent(&self) -> &[Option<String>] {
+        &self.recent
+    }
+
+    pub fn hotbar(&self) -> &[Option<String>] {
+        &self.hotbar
+    }
+
+    pub fn get_key(&self) -> &Option<String> {
+        &self
+            .palette
             .get(self.palette_index)
             .expect("Should not panic.")
+            .key
     }
 }
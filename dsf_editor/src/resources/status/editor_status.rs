@@ -1,4 +1,4 @@
-use crate::resources::{Brush, Selection};
+use crate::resources::{Blueprint, Brush, BrushMode, Selection};
 
 /// Contains some transient data related to the status of the editor.
 /// Holds things like the position of the cursor.
@@ -18,6 +18,26 @@ pub struct EditorStatus {
     /// If false, existing tiles will never be removed when placing tiles or pasting blueprints.
     ///     That means that it could happen that only part of the tiles are actually placed.
     pub force_place: bool,
+    /// The name of the level that is currently open, if any. Saving without choosing a name will
+    /// write to this level. If None, the level only exists as the auto save.
+    pub open_level: Option<String>,
+    /// The most recently copied or cut selection, if any.
+    pub clipboard: Option<Blueprint>,
+    /// If true, the clipboard is placed instead of the tile on the brush. Selecting a different
+    /// brush leaves paste mode.
+    pub pasting: bool,
+    /// A message for the level designer, shown in the editor's menu bar. Used to point out
+    /// problems, such as tiles that were left out when transforming the clipboard.
+    pub message: String,
+}
+
+impl EditorStatus {
+    /// Returns true if tiles that are in the way should be removed when placing tiles right now.
+    /// A flood fill replaces the region it fills, so it always removes the tiles in that region,
+    /// even if the force-place flag is off.
+    pub fn forces_placement(&self) -> bool {
+        self.force_place || (!self.pasting && self.brush.mode() == BrushMode::FloodFill)
+    }
 }
 
 impl Default for EditorStatus {
@@ -27,6 +47,10 @@ impl Default for EditorStatus {
             selection: Selection::default(),
             copy_air: true,
             force_place: true,
+            open_level: None,
+            clipboard: None,
+            pasting: false,
+            message: String::new(),
         }
     }
 }
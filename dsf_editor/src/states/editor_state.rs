@@ -5,16 +5,20 @@ use amethyst::{
     animation::{
         get_animation_set, AnimationCommand, AnimationControlSet, AnimationSet, EndControl,
     },
-    ecs::{prelude::World, Entities, Join, ReadStorage, WriteStorage},
+    ecs::{prelude::World, Entities, Entity, Join, ReadStorage, WriteStorage},
     input::{is_close_requested, is_key_down, InputEvent, VirtualKeyCode},
     prelude::*,
     renderer::sprite::SpriteRender,
+    ui::{UiEvent, UiEventType, UiTransform},
     StateData, Trans,
 };
 use dsf_precompile::AnimationId;
 
 use crate::resources::*;
-use crate::states::file_actions::{auto_save, auto_save_file, load_auto_save};
+use crate::states::file_actions::{
+    auto_save, auto_save_file, describe_issues, load_auto_save, save,
+};
+use crate::states::{FileDialogMode, FileDialogState, MetadataField};
 use crate::systems;
 
 use amethyst::core::ecs::shrev::EventChannel;
@@ -45,6 +49,11 @@ impl<'a, 'b> EditorState {
             is_active: false,
             dispatcher: DispatcherBuilder::new()
                 .with(systems::PlaceTilesSystem, "place_tile_system", &[])
+                .with(
+                    systems::MouseSystem::default(),
+                    "mouse_system",
+                    &["place_tile_system"],
+                )
                 .with_barrier()
                 .with(
                     systems::EditorUiUpdateSystem,
@@ -61,12 +70,22 @@ impl<'a, 'b> EditorState {
                     "animate_previews_system",
                     &[],
                 )
+                .with(
+                    systems::PalettePanelSystem::default(),
+                    "palette_panel_system",
+                    &[],
+                )
                 .with(systems::CursorSystem, "cursor_system", &[])
                 .with(
                     systems::UpdateBackgroundSystem,
                     "update_background_system",
                     &["cursor_system"],
                 )
+                .with(
+                    systems::EditorCameraSystem,
+                    "editor_camera_system",
+                    &["cursor_system"],
+                )
                 .with(
                     systems::SelectionSystem,
                     "selection_system",
@@ -82,7 +101,9 @@ impl<'a, 'b> EditorState {
     }
 
     /// Perform setup that should be executed both upon starting and upon resuming the State.
-    fn setup(&self, world: &mut World) {
+    /// The level is reloaded from the auto save, but the given edit history is kept, so changes
+    /// made before play testing or opening a dialog can still be undone.
+    fn setup(&self, world: &mut World, history: EditHistory) {
         init_cursor(world);
         UiHandles::add_ui(&UiType::Editor, world);
         setup_debug_lines(world);
@@ -92,9 +113,31 @@ impl<'a, 'b> EditorState {
             .write_resource::<EditorStatus>()
             .brush
             .set_palette(&tile_defs);
-        let level_edit = LevelEdit::new(load_auto_save(), tile_defs);
+        let mut level_edit = LevelEdit::new(load_auto_save(), tile_defs);
+        level_edit.history = history;
         add_background(world, &level_edit.tile_map.world_bounds);
         world.insert(level_edit);
+        systems::init_palette_panel(world);
+    }
+
+    /// Opens a dialog to save or open a level or blueprint by name, or to edit the metadata.
+    /// The editor reloads the auto save when it resumes, so the level is auto-saved first.
+    fn push_file_dialog(&self, mode: FileDialogMode, world: &mut World) -> SimpleTrans {
+        auto_save(world).expect("Failed to auto-save level!");
+        let initial_name = match mode {
+            FileDialogMode::Save | FileDialogMode::Open => world
+                .read_resource::<EditorStatus>()
+                .open_level
+                .clone()
+                .unwrap_or_default(),
+            FileDialogMode::SaveBlueprint
+            | FileDialogMode::OpenBlueprint
+            | FileDialogMode::FindTile => String::new(),
+            FileDialogMode::EditMetadata(field) => {
+                field.get(&world.read_resource::<LevelEdit>().metadata)
+            }
+        };
+        Trans::Push(Box::new(FileDialogState::new(mode, initial_name)))
     }
 }
 
@@ -108,7 +151,7 @@ impl SimpleState for EditorState {
         data.world.insert(readers);
         self.dispatcher.setup(data.world);
         data.world.insert(EditorStatus::default());
-        self.setup(data.world);
+        self.setup(data.world, EditHistory::default());
     }
 
     fn on_stop(&mut self, data: StateData<GameData>) {
@@ -127,7 +170,8 @@ impl SimpleState for EditorState {
     fn on_resume(&mut self, data: StateData<GameData>) {
         info!("EditorState on_resume");
         self.is_active = true;
-        self.setup(data.world);
+        let history = std::mem::take(&mut data.world.write_resource::<LevelEdit>().history);
+        self.setup(data.world, history);
     }
 
     fn handle_event(&mut self, data: StateData<GameData>, event: StateEvent) -> SimpleTrans {
@@ -143,6 +187,13 @@ impl SimpleState for EditorState {
                 }
             }
             // Ui event. Button presses, mouse hover, etc...
+            StateEvent::Ui(UiEvent {
+                event_type: UiEventType::Click,
+                target,
+            }) => {
+                select_clicked_palette_entry(target, data.world);
+                Trans::None
+            }
             StateEvent::Ui(_) => Trans::None,
             StateEvent::Input(input_event) => match input_event {
                 InputEvent::KeyReleased {
@@ -152,6 +203,61 @@ impl SimpleState for EditorState {
                     auto_save(data.world).expect("Failed to auto-save level!");
                     Trans::Push(Box::new(PlayState::new(auto_save_file())))
                 }
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F2,
+                    scancode: _,
+                } => {
+                    let open_level = data
+                        .world
+                        .read_resource::<EditorStatus>()
+                        .open_level
+                        .clone();
+                    if let Some(name) = open_level {
+                        // The level was opened or saved under this name before, so it is safe
+                        // to overwrite it.
+                        let message = match save(&name, true, data.world) {
+                            Ok(issues) => {
+                                info!("Saved level {:?}", name);
+                                describe_issues(format!("Saved level {:?}.", name), &issues)
+                            }
+                            Err(error) => {
+                                error!("Failed to save level {:?}: {}", name, error);
+                                format!("Failed to save level {:?}: {}", name, error)
+                            }
+                        };
+                        data.world.write_resource::<EditorStatus>().message = message;
+                        Trans::None
+                    } else {
+                        self.push_file_dialog(FileDialogMode::Save, data.world)
+                    }
+                }
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F3,
+                    scancode: _,
+                } => self.push_file_dialog(FileDialogMode::Save, data.world),
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F4,
+                    scancode: _,
+                } => self.push_file_dialog(FileDialogMode::Open, data.world),
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F6,
+                    scancode: _,
+                } => self.push_file_dialog(FileDialogMode::SaveBlueprint, data.world),
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F7,
+                    scancode: _,
+                } => self.push_file_dialog(FileDialogMode::OpenBlueprint, data.world),
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F8,
+                    scancode: _,
+                } => self.push_file_dialog(FileDialogMode::FindTile, data.world),
+                InputEvent::KeyReleased {
+                    key_code: VirtualKeyCode::F9,
+                    scancode: _,
+                } => self.push_file_dialog(
+                    FileDialogMode::EditMetadata(MetadataField::Title),
+                    data.world,
+                ),
                 _ => Trans::None,
             },
         }
@@ -204,6 +310,26 @@ impl SimpleState for EditorState {
     }
 }
 
+/// If the clicked UI element is a palette entry, put that tile on the brush.
+fn select_clicked_palette_entry(target: Entity, world: &mut World) {
+    let index = world
+        .read_storage::<UiTransform>()
+        .get(target)
+        .and_then(|transform| systems::palette_slot_index(&transform.id));
+    if let Some(index) = index {
+        let selected = {
+            let mut status = world.write_resource::<EditorStatus>();
+            status.pasting = false;
+            status.brush.select_index(index)
+        };
+        if selected {
+            world
+                .write_resource::<EventChannel<RefreshPreviewsEvent>>()
+                .single_write(RefreshPreviewsEvent);
+        }
+    }
+}
+
 /// TODO: Temporary function, clean up later.
 /// Adds a selection and a cursor entity.
 fn init_cursor(world: &mut World) {
@@ -1,11 +1,41 @@
-use crate::resources::LevelEdit;
+use crate::resources::{Blueprint, LevelEdit};
 use amethyst::config::ConfigError;
 use amethyst::prelude::{Config, World, WorldExt};
 
-use dsf_core::levels::LevelSave;
-use dsf_core::utility::files::get_levels_dir;
+use dsf_core::levels::{
+    load_level_save, save_level_file, validate, LevelIssue, LevelSave, LEVEL_TEXT_EXTENSION,
+    RESERVED_LEVEL_NAMES,
+};
+use dsf_core::resources::TileDefinitions;
+use dsf_core::utility::files::{get_blueprints_dir, get_levels_dir};
+use std::fmt;
+use std::fs;
 use std::path::PathBuf;
 
+/// The reasons why a level or blueprint could not be saved under a given name.
+#[derive(Debug)]
+pub enum SaveError {
+    /// The name is empty or contains characters other than letters, digits, '_' and '-'.
+    InvalidName,
+    /// The name is reserved for the editor's own use, such as the auto save.
+    ReservedName,
+    /// A file with this name already exists and overwriting was not allowed.
+    AlreadyExists,
+    /// Writing the file failed.
+    Config(ConfigError),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::InvalidName => write!(f, "Use only letters, digits, '_' and '-'."),
+            SaveError::ReservedName => write!(f, "That name is reserved."),
+            SaveError::AlreadyExists => write!(f, "That name is already in use."),
+            SaveError::Config(error) => write!(f, "Failed to write file: {:?}", error),
+        }
+    }
+}
+
 /// Returns a PathBuf to the file that is used to store auto saves.
 pub fn auto_save_file() -> PathBuf {
     get_levels_dir().join("auto_save.ron")
@@ -18,7 +48,7 @@ pub fn load_auto_save() -> LevelSave {
     if level_file.exists() {
         // We want to panic if the auto-save level can't be loaded.
         // Otherwise we risk accidentally overriding the existing auto-save level.
-        LevelSave::load(level_file).expect(
+        load_level_save(level_file).expect(
             "Failed to load assets/world/levels/auto-save.ron. \
             It is probably corrupted somehow. You could try to rename or delete it, \
             the editor will then recreate a new, empty auto-save level.",
@@ -28,31 +58,174 @@ pub fn load_auto_save() -> LevelSave {
     }
 }
 
+/// Returns the file of the level with the given name. Levels can be stored in RON or in the text
+/// format. If there is a level in the text format with this name, that file is used, otherwise
+/// the RON file is used. New levels are saved as RON.
+fn level_file(name: &str) -> PathBuf {
+    let text_file = get_levels_dir().join(format!("{}.{}", name, LEVEL_TEXT_EXTENSION));
+    if text_file.exists() {
+        text_file
+    } else {
+        get_levels_dir().join(name.to_string() + ".ron")
+    }
+}
+
 /// Load and return the level with the given name.
-#[allow(dead_code)] //Not used yet, but will be used in the future.
-pub fn load(name: String) -> Result<LevelSave, ConfigError> {
-    let level_file = get_levels_dir().join(name + ".ron");
-    LevelSave::load(level_file)
+pub fn load(name: &str) -> Result<LevelSave, ConfigError> {
+    load_level_save(level_file(name))
+}
+
+/// Open the level with the given name for editing. The editor always works on the auto save
+/// file, so this copies the level over the auto save. The editor will show the opened level once
+/// it (re)loads the auto save.
+///
+/// Returns the issues that the opened level has, so they can be shown to the designer.
+pub fn open(name: &str, tile_defs: &TileDefinitions) -> Result<Vec<LevelIssue>, ConfigError> {
+    let level = load(name)?;
+    level.write(auto_save_file())?;
+    Ok(validate(&level, tile_defs))
+}
+
+/// Describes what happened to a level and lists the issues it has, for display in the editor.
+pub fn describe_issues(what_happened: String, issues: &[LevelIssue]) -> String {
+    if issues.is_empty() {
+        what_happened
+    } else {
+        let issues = issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+        format!(
+            "{} It has {} issue(s): {}.",
+            what_happened,
+            issues.len(),
+            issues.join("; ")
+        )
+    }
+}
+
+/// Returns true if the level that is being edited differs from the level file it was last opened
+/// from or saved to. A level that was never saved counts as unsaved as soon as it has any tiles or
+/// metadata.
+pub fn has_unsaved_changes(open_level: Option<&str>, level_edit: &LevelEdit) -> bool {
+    let current: LevelSave = level_edit.clone().into();
+    match open_level.map(load) {
+        Some(Ok(saved)) => {
+            saved.tiles != current.tiles
+                || saved.world_bounds != current.world_bounds
+                || saved.metadata != current.metadata
+        }
+        // If the saved level can no longer be read, it cannot be compared against.
+        Some(Err(_)) => true,
+        None => !current.tiles.is_empty() || !current.metadata.is_empty(),
+    }
+}
+
+/// Returns the names of all levels in the levels directory, sorted alphabetically.
+/// Reserved names, such as the auto save, are not included.
+pub fn list_levels() -> Vec<String> {
+    list_files(get_levels_dir(), &["ron", LEVEL_TEXT_EXTENSION])
+        .drain(..)
+        .filter(|name| !RESERVED_LEVEL_NAMES.contains(&name.as_str()))
+        .collect()
+}
+
+/// Returns the names of all blueprints in the blueprints directory, sorted alphabetically.
+pub fn list_blueprints() -> Vec<String> {
+    list_files(get_blueprints_dir(), &["ron"])
+}
+
+/// Returns the names (without extension) of all files in the given directory that have one of the
+/// given extensions, sorted alphabetically.
+fn list_files(dir: PathBuf, extensions: &[&str]) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    path.extension()
+                        .map(|ext| extensions.iter().any(|extension| ext == *extension))
+                        .unwrap_or(false)
+                })
+                .filter_map(|path| {
+                    path.file_stem()
+                        .map(|stem| stem.to_string_lossy().to_string())
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Load and return the blueprint with the given name.
+pub fn load_blueprint(name: &str) -> Result<Blueprint, ConfigError> {
+    Blueprint::load(get_blueprints_dir().join(name.to_string() + ".ron"))
+}
+
+/// Store the given blueprint to file. The given name will be used as a filename.
+///
+/// If a blueprint with the given name already exists, it is only overwritten if `overwrite` is
+/// true.
+pub fn save_blueprint(name: &str, overwrite: bool, blueprint: &Blueprint) -> Result<(), SaveError> {
+    check_name(name)?;
+    let blueprint_file = get_blueprints_dir().join(name.to_string() + ".ron");
+    if blueprint_file.exists() && !overwrite {
+        return Err(SaveError::AlreadyExists);
+    }
+    blueprint.write(blueprint_file).map_err(SaveError::Config)
 }
 
 /// Write the current state of the LevelEdit to the auto save file, overwriting what is already
 /// there.
 pub fn auto_save(world: &mut World) -> Result<(), ConfigError> {
-    write_level_file(auto_save_file(), world)
+    write_level_file(auto_save_file(), world).map(|_| ())
 }
 
 /// Store the current state of the LevelEdit to file. The given name will be used as a filename.
-/// TODO: check if name is reserved (ie: auto_save)
-/// TODO: check if level already exists, if so maybe ask to overwrite?
-///     (or keep track of which one we loaded, so we know whether it's safe to overwrite)
-#[allow(dead_code)] //Not used yet, but will be used in the future.
-pub fn save(name: String, world: &mut World) -> Result<(), ConfigError> {
-    let level_file = get_levels_dir().join(name + ".ron");
-    write_level_file(level_file, world)
+///
+/// Refuses to use reserved names or names that are not valid file names. If a level with the
+/// given name already exists, it is only overwritten if `overwrite` is true.
+///
+/// Returns the issues that the saved level has, so they can be shown to the designer.
+pub fn save(name: &str, overwrite: bool, world: &mut World) -> Result<Vec<LevelIssue>, SaveError> {
+    check_name(name)?;
+    let level_file = level_file(name);
+    if level_file.exists() && !overwrite {
+        return Err(SaveError::AlreadyExists);
+    }
+    write_level_file(level_file, world).map_err(SaveError::Config)
+}
+
+/// Checks whether the given name can be used to save a level or blueprint under.
+pub fn check_name(name: &str) -> Result<(), SaveError> {
+    if RESERVED_LEVEL_NAMES.contains(&name) {
+        Err(SaveError::ReservedName)
+    } else if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        Err(SaveError::InvalidName)
+    } else {
+        Ok(())
+    }
 }
 
-fn write_level_file(level_file: PathBuf, world: &mut World) -> Result<(), ConfigError> {
+/// Writes the level to file. Any issues with the level are logged and returned, but the level is
+/// saved regardless: a level that is still being worked on is rarely complete.
+fn write_level_file(
+    level_file: PathBuf,
+    world: &mut World,
+) -> Result<Vec<LevelIssue>, ConfigError> {
     let level_edit = world.write_resource::<LevelEdit>();
     let level: LevelSave = (*level_edit).clone().into();
-    level.write(level_file)
+    let issues = validate(&level, &level_edit.tile_map.tile_defs);
+    issues.iter().for_each(|issue| {
+        warn!("Level {:?} has an issue: {}", level_file, issue);
+    });
+    save_level_file(level_file, &level, &level_edit.tile_map.tile_defs)?;
+    Ok(issues)
 }
@@ -0,0 +1,402 @@
+use amethyst::prelude::WorldExt;
+
+use amethyst::core::ecs::{Entity, ReadStorage, World, WriteStorage};
+use amethyst::StateEvent;
+use amethyst::{
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    ui::{UiFinder, UiText},
+    GameData, SimpleState, SimpleTrans, StateData, Trans,
+};
+
+use crate::resources::{Blueprint, EditHistory, EditorStatus, LevelEdit};
+use crate::states::file_actions::{
+    auto_save, describe_issues, has_unsaved_changes, list_blueprints, list_levels, load_blueprint,
+    open, save, save_blueprint, SaveError,
+};
+use dsf_core::levels::LevelMetadata;
+use dsf_core::resources::{UiHandles, UiType};
+use dsf_core::states::window_event_handler;
+
+const NAME_INPUT_ID: &str = "editable";
+const MESSAGE_LABEL_ID: &str = "dialog_message";
+
+/// What the file dialog will do with the chosen name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileDialogMode {
+    /// Save the level that is being edited under the chosen name.
+    Save,
+    /// Open the level with the chosen name for editing.
+    Open,
+    /// Save the current selection as a blueprint under the chosen name.
+    SaveBlueprint,
+    /// Put the blueprint with the chosen name on the clipboard, so it can be stamped into the level.
+    OpenBlueprint,
+    /// Put the first tile whose name contains the entered text on the brush.
+    FindTile,
+    /// Change the given field of the level's metadata. After confirming, the dialog moves on to
+    /// the next field.
+    EditMetadata(MetadataField),
+}
+
+/// The fields of the level metadata, in the order in which the dialog asks for them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MetadataField {
+    Title,
+    Author,
+    Description,
+    Difficulty,
+    Hint,
+    ParMoves,
+}
+
+impl MetadataField {
+    fn next(self) -> Option<Self> {
+        match self {
+            MetadataField::Title => Some(MetadataField::Author),
+            MetadataField::Author => Some(MetadataField::Description),
+            MetadataField::Description => Some(MetadataField::Difficulty),
+            MetadataField::Difficulty => Some(MetadataField::Hint),
+            MetadataField::Hint => Some(MetadataField::ParMoves),
+            MetadataField::ParMoves => None,
+        }
+    }
+
+    fn prompt(self) -> &'static str {
+        match self {
+            MetadataField::Title => "Level title:",
+            MetadataField::Author => "Author:",
+            MetadataField::Description => "Description:",
+            MetadataField::Difficulty => "Difficulty (Easy, Medium, Hard, Expert or empty):",
+            MetadataField::Hint => "Hint:",
+            MetadataField::ParMoves => "Par moves (a number or empty):",
+        }
+    }
+
+    /// Returns the current value of this field as text, so it can be edited.
+    pub fn get(self, metadata: &LevelMetadata) -> String {
+        match self {
+            MetadataField::Title => metadata.title.clone(),
+            MetadataField::Author => metadata.author.clone(),
+            MetadataField::Description => metadata.description.clone(),
+            MetadataField::Difficulty => metadata
+                .difficulty
+                .map(|difficulty| difficulty.to_string())
+                .unwrap_or_default(),
+            MetadataField::Hint => metadata.hint.clone(),
+            MetadataField::ParMoves => metadata
+                .par_moves
+                .map(|par| par.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parses the given text and stores it in this field. Empty text clears the field.
+    /// Returns a message for the user if the text is not a valid value for this field.
+    fn set(self, metadata: &mut LevelMetadata, value: &str) -> Result<(), String> {
+        match self {
+            MetadataField::Title => metadata.title = value.to_string(),
+            MetadataField::Author => metadata.author = value.to_string(),
+            MetadataField::Description => metadata.description = value.to_string(),
+            MetadataField::Difficulty => {
+                metadata.difficulty = if value.is_empty() {
+                    None
+                } else {
+                    Some(value.parse()?)
+                }
+            }
+            MetadataField::Hint => metadata.hint = value.to_string(),
+            MetadataField::ParMoves => {
+                metadata.par_moves = if value.is_empty() {
+                    None
+                } else {
+                    Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("{:?} is not a number of moves.", value))?,
+                    )
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A dialog that asks the user for the name of a level or blueprint, so it can be saved or opened,
+/// or for the name of a tile to put on the brush. It is also used to edit the level's metadata.
+/// Is pushed on top of the EditorState. Press Enter to confirm, Escape to cancel.
+pub struct FileDialogState {
+    mode: FileDialogMode,
+    /// The name that the input field starts out with.
+    initial_name: String,
+    /// If the user tries to save under the name of an existing file, or to open a level while the
+    /// current level has unsaved changes, they are asked to confirm. This holds the name they must
+    /// confirm.
+    confirm_overwrite: Option<String>,
+    name_input: Option<Entity>,
+    message_label: Option<Entity>,
+}
+
+impl FileDialogState {
+    pub fn new(mode: FileDialogMode, initial_name: String) -> Self {
+        FileDialogState {
+            mode,
+            initial_name,
+            confirm_overwrite: None,
+            name_input: None,
+            message_label: None,
+        }
+    }
+
+    fn init_ui(&mut self, data: StateData<GameData>) {
+        UiHandles::add_ui(&UiType::Save, data.world);
+        // invoke a world update to finish creating our ui entities
+        data.data.update(&data.world);
+        data.world.exec(|ui_finder: UiFinder<'_>| {
+            self.name_input = ui_finder.find(NAME_INPUT_ID);
+            self.message_label = ui_finder.find(MESSAGE_LABEL_ID);
+        });
+        let initial_name = self.initial_name.clone();
+        self.set_text(self.name_input, initial_name, data.world);
+        let prompt = match self.mode {
+            FileDialogMode::Save => "Save level as:".to_string(),
+            FileDialogMode::Open => format!("Open level: {}", list_levels().join(", ")),
+            FileDialogMode::SaveBlueprint => "Save selection as blueprint:".to_string(),
+            FileDialogMode::OpenBlueprint => {
+                format!("Stamp blueprint: {}", list_blueprints().join(", "))
+            }
+            FileDialogMode::FindTile => {
+                let status = data.world.read_resource::<EditorStatus>();
+                let labels = status
+                    .brush
+                    .palette()
+                    .iter()
+                    .map(|entry| entry.label().to_string())
+                    .collect::<Vec<_>>();
+                format!("Find tile: {}", labels.join(", "))
+            }
+            FileDialogMode::EditMetadata(field) => field.prompt().to_string(),
+        };
+        self.set_text(self.message_label, prompt, data.world);
+    }
+
+    fn set_text(&self, entity: Option<Entity>, text: String, world: &mut World) {
+        if let Some(entity) = entity {
+            world.exec(|mut ui_text: WriteStorage<UiText>| {
+                if let Some(text_component) = ui_text.get_mut(entity) {
+                    text_component.text = text;
+                }
+            });
+        }
+    }
+
+    fn entered_name(&self, world: &mut World) -> String {
+        self.name_input
+            .and_then(|entity| {
+                world.exec(|ui_text: ReadStorage<UiText>| {
+                    ui_text.get(entity).map(|text| text.text.trim().to_string())
+                })
+            })
+            .unwrap_or_default()
+    }
+
+    /// Attempts to save or open the level with the entered name. Returns true if the dialog is
+    /// done and can be closed.
+    fn confirm(&mut self, world: &mut World) -> bool {
+        let name = self.entered_name(world);
+        match self.mode {
+            FileDialogMode::Save => {
+                let overwrite = self.confirm_overwrite.as_ref() == Some(&name)
+                    || world.read_resource::<EditorStatus>().open_level.as_ref() == Some(&name);
+                match save(&name, overwrite, world) {
+                    Ok(issues) => {
+                        info!("Saved level {:?}", name);
+                        let mut status = world.write_resource::<EditorStatus>();
+                        status.message =
+                            describe_issues(format!("Saved level {:?}.", name), &issues);
+                        status.open_level = Some(name);
+                        true
+                    }
+                    Err(error) => self.handle_save_error(name, error, world),
+                }
+            }
+            FileDialogMode::Open => {
+                let confirmed = self.confirm_overwrite.as_ref() == Some(&name);
+                if !confirmed && self.would_discard_changes(world) {
+                    let message = format!(
+                        "The level has unsaved changes. Press Enter again to open {:?} anyway.",
+                        name
+                    );
+                    self.confirm_overwrite = Some(name);
+                    self.set_text(self.message_label, message, world);
+                    return false;
+                }
+                self.open_level(name, world)
+            }
+            FileDialogMode::SaveBlueprint => {
+                let overwrite = self.confirm_overwrite.as_ref() == Some(&name);
+                let blueprint = Blueprint::from_selection(
+                    &world.read_resource::<EditorStatus>(),
+                    &world.read_resource::<LevelEdit>(),
+                );
+                match save_blueprint(&name, overwrite, &blueprint) {
+                    Ok(()) => {
+                        info!("Saved blueprint {:?}", name);
+                        true
+                    }
+                    Err(error) => self.handle_save_error(name, error, world),
+                }
+            }
+            FileDialogMode::OpenBlueprint => match load_blueprint(&name) {
+                Ok(blueprint) => {
+                    info!("Loaded blueprint {:?}", name);
+                    let mut status = world.write_resource::<EditorStatus>();
+                    status.clipboard = Some(blueprint);
+                    status.pasting = true;
+                    true
+                }
+                Err(error) => {
+                    error!("Failed to load blueprint {:?}: {:?}", name, error);
+                    let message = format!("Could not load blueprint {:?}.", name);
+                    self.set_text(self.message_label, message, world);
+                    false
+                }
+            },
+            FileDialogMode::FindTile => {
+                let found = {
+                    let mut status = world.write_resource::<EditorStatus>();
+                    match status.brush.find(&name) {
+                        Some(index) => {
+                            status.brush.select_index(index);
+                            status.pasting = false;
+                            true
+                        }
+                        None => false,
+                    }
+                };
+                if !found {
+                    let message = format!("No tile matches {:?}.", name);
+                    self.set_text(self.message_label, message, world);
+                }
+                found
+            }
+            FileDialogMode::EditMetadata(field) => {
+                let result = field.set(&mut world.write_resource::<LevelEdit>().metadata, &name);
+                match result {
+                    Ok(()) => {
+                        // The editor reloads the auto save when it resumes, so the change is
+                        // lost unless the auto save succeeds.
+                        if let Err(error) = auto_save(world) {
+                            error!("Failed to auto-save level: {:?}", error);
+                            return self.handle_save_error(name, SaveError::Config(error), world);
+                        }
+                        match field.next() {
+                            Some(next) => {
+                                self.show_metadata_field(next, world);
+                                false
+                            }
+                            None => true,
+                        }
+                    }
+                    Err(message) => {
+                        self.set_text(self.message_label, message, world);
+                        false
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns true if opening another level would throw away changes to the current level that
+    /// were not saved to its level file.
+    fn would_discard_changes(&self, world: &mut World) -> bool {
+        let status = world.read_resource::<EditorStatus>();
+        has_unsaved_changes(
+            status.open_level.as_deref(),
+            &world.read_resource::<LevelEdit>(),
+        )
+    }
+
+    /// Opens the level with the given name. Returns true if the dialog can be closed.
+    fn open_level(&mut self, name: String, world: &mut World) -> bool {
+        let result = open(
+            &name,
+            &world.read_resource::<LevelEdit>().tile_map.tile_defs,
+        );
+        match result {
+            Ok(issues) => {
+                info!("Opened level {:?}", name);
+                // The history belongs to the level that was open before and cannot be
+                // applied to the newly opened one.
+                world.write_resource::<LevelEdit>().history = EditHistory::default();
+                let mut status = world.write_resource::<EditorStatus>();
+                status.message = describe_issues(format!("Opened level {:?}.", name), &issues);
+                status.open_level = Some(name);
+                true
+            }
+            Err(error) => {
+                error!("Failed to open level {:?}: {:?}", name, error);
+                let message = format!("Could not open level {:?}.", name);
+                self.set_text(self.message_label, message, world);
+                false
+            }
+        }
+    }
+
+    /// Switches the dialog over to editing the given metadata field.
+    fn show_metadata_field(&mut self, field: MetadataField, world: &mut World) {
+        self.mode = FileDialogMode::EditMetadata(field);
+        let value = field.get(&world.read_resource::<LevelEdit>().metadata);
+        self.set_text(self.name_input, value, world);
+        self.set_text(self.message_label, field.prompt().to_string(), world);
+    }
+
+    /// Shows why saving failed. If the name is already in use, the user can press Enter again to
+    /// overwrite the existing file. Always returns false, because the dialog stays open.
+    fn handle_save_error(&mut self, name: String, error: SaveError, world: &mut World) -> bool {
+        match error {
+            SaveError::AlreadyExists => {
+                let message = format!(
+                    "{:?} already exists. Press Enter again to overwrite it.",
+                    name
+                );
+                self.confirm_overwrite = Some(name);
+                self.set_text(self.message_label, message, world);
+            }
+            error => {
+                self.confirm_overwrite = None;
+                self.set_text(self.message_label, error.to_string(), world);
+            }
+        }
+        false
+    }
+}
+
+impl SimpleState for FileDialogState {
+    fn on_start(&mut self, data: StateData<GameData>) {
+        info!("FileDialogState on_start");
+        self.init_ui(data);
+    }
+
+    fn on_stop(&mut self, data: StateData<GameData>) {
+        info!("FileDialogState on_stop");
+        data.world.delete_all();
+    }
+
+    fn handle_event(&mut self, data: StateData<GameData>, event: StateEvent) -> SimpleTrans {
+        window_event_handler::handle(&event, data.world);
+        match event {
+            StateEvent::Window(event) => {
+                let cancelled =
+                    is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape);
+                if cancelled
+                    || (is_key_down(&event, VirtualKeyCode::Return) && self.confirm(data.world))
+                {
+                    Trans::Pop
+                } else {
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+}
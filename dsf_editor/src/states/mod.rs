@@ -1,4 +1,6 @@
 mod editor_state;
 mod file_actions;
+mod file_dialog;
 
 pub use self::editor_state::EditorState;
+pub use self::file_dialog::{FileDialogMode, FileDialogState, MetadataField};
@@ -1,18 +1,21 @@
-use crate::resources::{EditorStatus, LevelEdit};
-use crate::systems::RefreshPreviewsEvent;
+use crate::resources::{EditorStatus, LevelEdit, HOTBAR_SLOTS};
+use crate::systems::{brush_image, RefreshPreviewsEvent};
 use amethyst::core::ecs::shrev::EventChannel;
 use amethyst::core::ecs::{Read, System, Write, WriteStorage};
 use amethyst::input::{InputHandler, StringBindings};
-use amethyst::ui::{UiFinder, UiImage};
+use amethyst::ui::{UiFinder, UiImage, UiText};
 use dsf_core::levels::load_sprite_render;
-use dsf_core::resources::{AssetType, Assets, SignalEdge, SignalEdgeDetector, SpriteType};
+use dsf_core::resources::{Assets, SignalEdge, SignalEdgeDetector, SpriteType};
 
 /// Responsible for changing transient configurations for the editor. These settings stay alive
 /// as long as the EditorState lives.
 ///
 /// Currently, this system is responsible for:
 ///
-/// - Changing what tile is on the brush.
+/// - Changing what tile is on the brush, including through the hotbar.
+/// - Assigning tiles to hotbar slots.
+/// - Picking the tile under the cursor (eyedropper).
+/// - Changing the brush mode.
 /// - Toggling the copy-air flag.
 /// - Toggling the force-place flag.
 ///
@@ -25,15 +28,50 @@ impl<'s> System<'s> for ConfigureEditorSystem {
         Read<'s, InputHandler<StringBindings>>,
         Write<'s, SignalEdgeDetector>,
         Write<'s, EditorStatus>,
+        Read<'s, LevelEdit>,
     );
 
-    fn run(&mut self, (mut channel, input, mut sed, mut status): Self::SystemData) {
+    fn run(&mut self, (mut channel, input, mut sed, mut status, level_edit): Self::SystemData) {
         if let SignalEdge::Rising = sed.edge("select_previous_brush", &input) {
             let _new_key = status.brush.select_previous();
+            status.pasting = false;
             channel.single_write(RefreshPreviewsEvent);
         }
         if let SignalEdge::Rising = sed.edge("select_next_brush", &input) {
             let _new_key = status.brush.select_next();
+            status.pasting = false;
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("pick_brush", &input) {
+            let key = level_edit
+                .tile_key_at(&status.selection.end)
+                .map(|key| key.to_string());
+            if status.brush.select_key(&key) {
+                status.pasting = false;
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+        if let SignalEdge::Rising = sed.edge("select_last_used_brush", &input) {
+            if status.brush.select_last_used() {
+                status.pasting = false;
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+        for slot in 0..HOTBAR_SLOTS {
+            // Assign first: the select action is also down while the assign action is down.
+            if let SignalEdge::Rising = sed.edge(&format!("assign_hotbar_{}", slot + 1), &input) {
+                status.brush.assign_hotbar(slot);
+            }
+            if let SignalEdge::Rising = sed.edge(&format!("hotbar_{}", slot + 1), &input) {
+                if status.brush.select_hotbar(slot) {
+                    status.pasting = false;
+                    channel.single_write(RefreshPreviewsEvent);
+                }
+            }
+        }
+        if let SignalEdge::Rising = sed.edge("select_next_brush_mode", &input) {
+            let _new_mode = status.brush.select_next_mode();
+            status.pasting = false;
             channel.single_write(RefreshPreviewsEvent);
         }
         if let SignalEdge::Rising = sed.edge("toggle_copy_air", &input) {
@@ -47,20 +85,25 @@ impl<'s> System<'s> for ConfigureEditorSystem {
     }
 }
 
-/// Updates the UI images for the copy-air and force-place flags and for the active brush.
+/// Updates the UI images for the copy-air and force-place flags and for the active brush, and
+/// shows the editor's message to the designer.
 pub struct EditorUiUpdateSystem;
 
 impl<'s> System<'s> for EditorUiUpdateSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteStorage<'s, UiImage>,
+        WriteStorage<'s, UiText>,
         UiFinder<'s>,
         Read<'s, EditorStatus>,
         Read<'s, LevelEdit>,
         Read<'s, Assets>,
     );
 
-    fn run(&mut self, (mut ui_image, finder, status, level_edit, assets): Self::SystemData) {
+    fn run(
+        &mut self,
+        (mut ui_image, mut ui_text, finder, status, level_edit, assets): Self::SystemData,
+    ) {
         let toggle_copy_air = get_image("toggle_copy_air", &finder, &mut ui_image);
         if let Some(toggle_copy_air) = toggle_copy_air {
             let sprite_nr = if status.copy_air { 0 } else { 1 };
@@ -81,23 +124,14 @@ impl<'s> System<'s> for EditorUiUpdateSystem {
         }
         let brush_preview = get_image("brush_preview", &finder, &mut ui_image);
         if let Some(brush_preview) = brush_preview {
-            if let Some(sprite_render) = status
-                .brush
-                .get_key()
-                .as_ref()
-                .map(|selected_key| level_edit.get_tile_def(selected_key))
-                .map(|tile_def| {
-                    if let AssetType::Still(sprite, sprite_nr) = tile_def.get_preview() {
-                        Some(load_sprite_render(&sprite, sprite_nr, &assets))
-                    } else {
-                        None
-                    }
-                })
-                .flatten()
-            {
-                *brush_preview = UiImage::Sprite(sprite_render);
-            } else {
-                *brush_preview = UiImage::SolidColor([0.0, 0.0, 0.0, 1.0]);
+            *brush_preview = brush_image(status.brush.get_key(), &level_edit, &assets);
+        }
+        let message = finder
+            .find("editor_message")
+            .and_then(|entity| ui_text.get_mut(entity));
+        if let Some(message) = message {
+            if message.text != status.message {
+                message.text = status.message.clone();
             }
         }
     }
@@ -93,12 +93,8 @@ impl<'s> System<'s> for CursorSystem {
             }
             if should_move {
                 if adjust_bounds {
-                    level_edit
-                        .bounds_mut()
-                        .adjust_x(status.selection.end.x, input_x as i32);
-                    level_edit
-                        .bounds_mut()
-                        .adjust_y(status.selection.end.y, input_y as i32);
+                    level_edit.adjust_bounds(status.selection.end, input_x as i32, input_y as i32);
+                    level_edit.commit_edit();
                 }
                 status.selection.end.x += input_x as i32;
                 status.selection.end.y += input_y as i32;
@@ -0,0 +1,35 @@
+use amethyst::core::ecs::{Join, Read, ReadExpect, System, WriteStorage};
+use amethyst::core::Transform;
+use amethyst::window::ScreenDimensions;
+
+use dsf_core::components::CameraFrame;
+use dsf_core::systems::fit_to_bounds;
+
+use crate::resources::LevelEdit;
+
+/// Positions the camera in the editor. Normally, the camera stays at the origin. In
+/// fit-whole-level mode, it centers on the level and zooms to show all of it.
+pub struct EditorCameraSystem;
+
+impl<'s> System<'s> for EditorCameraSystem {
+    type SystemData = (
+        WriteStorage<'s, CameraFrame>,
+        WriteStorage<'s, Transform>,
+        Read<'s, LevelEdit>,
+        ReadExpect<'s, ScreenDimensions>,
+    );
+
+    fn run(
+        &mut self,
+        (mut camera_frames, mut transforms, level_edit, screen_dimens): Self::SystemData,
+    ) {
+        for (frame, transform) in (&mut camera_frames, &mut transforms).join() {
+            if frame.fit_level {
+                fit_to_bounds(frame, transform, level_edit.bounds(), &screen_dimens);
+            } else {
+                transform.set_translation_x(0.);
+                transform.set_translation_y(0.);
+            }
+        }
+    }
+}
@@ -1,5 +1,8 @@
 mod configure_editor;
 mod cursor;
+mod editor_camera;
+mod mouse;
+mod palette_panel;
 mod place_tiles;
 mod preview_animation;
 mod refresh_previews;
@@ -9,6 +12,9 @@ mod update_background;
 
 pub use self::configure_editor::*;
 pub use self::cursor::*;
+pub use self::editor_camera::*;
+pub use self::mouse::*;
+pub use self::palette_panel::*;
 pub use self::place_tiles::*;
 pub use self::preview_animation::*;
 pub use self::refresh_previews::*;
@@ -0,0 +1,121 @@
+use amethyst::core::ecs::{Join, Read, ReadExpect, ReadStorage, System, Write};
+use amethyst::core::math::{Point3, Vector2};
+use amethyst::core::Transform;
+use amethyst::input::{InputHandler, StringBindings};
+use amethyst::renderer::Camera;
+use amethyst::shrev::EventChannel;
+use amethyst::ui::UiTransform;
+use amethyst::window::ScreenDimensions;
+
+use dsf_core::components::Pos;
+use dsf_core::resources::{SignalEdge, SignalEdgeDetector};
+
+use crate::resources::{EditorStatus, LevelEdit};
+use crate::systems::{paint, RefreshPreviewsEvent};
+
+/// Lets the user control the editor with the mouse.
+///
+/// - Moving the mouse onto a different tile moves the cursor to that tile.
+/// - Dragging with the left mouse button selects an area.
+/// - Clicking with the left mouse button, without dragging, paints with the brush.
+/// - Clicking with the right mouse button erases the tile under the mouse.
+///
+/// The mouse is ignored while it hovers over the editor's UI.
+#[derive(Default)]
+pub struct MouseSystem {
+    /// The mouse position on screen during the previous frame.
+    last_mouse_position: Option<(f32, f32)>,
+    /// The tile that the mouse was last seen over. The cursor only follows the mouse when it
+    /// moves onto a different tile, so small movements do not fight with the keyboard controls.
+    last_hovered: Option<Pos>,
+    /// Where the left mouse button was pressed, if it is currently held down over the level.
+    drag_start: Option<Pos>,
+}
+
+impl<'s> System<'s> for MouseSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Write<'s, EventChannel<RefreshPreviewsEvent>>,
+        Write<'s, SignalEdgeDetector>,
+        Read<'s, InputHandler<StringBindings>>,
+        ReadExpect<'s, ScreenDimensions>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, UiTransform>,
+        Write<'s, EditorStatus>,
+        Write<'s, LevelEdit>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            mut channel,
+            mut sed,
+            input,
+            screen_dimens,
+            cameras,
+            transforms,
+            ui_transforms,
+            mut status,
+            mut level_edit,
+        ): Self::SystemData,
+    ) {
+        let paint_edge = sed.edge("mouse_paint", &input);
+        let erase_edge = sed.edge("mouse_erase", &input);
+        let mouse_position = match input.mouse_position() {
+            Some(mouse_position) => mouse_position,
+            None => return,
+        };
+        let mouse_moved = self.last_mouse_position != Some(mouse_position);
+        self.last_mouse_position = Some(mouse_position);
+        let over_ui = (&ui_transforms).join().any(|ui_transform| {
+            // UI coordinates start at the bottom of the screen, mouse coordinates at the top.
+            ui_transform
+                .position_inside(mouse_position.0, screen_dimens.height() - mouse_position.1)
+        });
+        if over_ui && self.drag_start.is_none() {
+            return;
+        }
+        let hovered = match (&cameras, &transforms).join().next() {
+            Some((camera, camera_transform)) => {
+                let world_point = camera.screen_to_world_point(
+                    Point3::new(mouse_position.0, mouse_position.1, 0.0),
+                    Vector2::new(screen_dimens.width(), screen_dimens.height()),
+                    camera_transform,
+                );
+                let pos = Pos::new(world_point.x.floor() as i32, world_point.y.floor() as i32);
+                level_edit.bounds().clamp(&pos)
+            }
+            None => return,
+        };
+
+        // The view can also scroll while the mouse stands still, which changes the tile under the
+        // mouse. That should not move the cursor either.
+        let moved_to_other_tile = mouse_moved && self.last_hovered != Some(hovered);
+        self.last_hovered = Some(hovered);
+
+        let old_selection = (status.selection.start, status.selection.end);
+        if let SignalEdge::Rising = paint_edge {
+            self.drag_start = Some(hovered);
+        }
+        if moved_to_other_tile || self.drag_start.is_some() {
+            status.selection.end = hovered;
+            status.selection.start = self.drag_start.unwrap_or(hovered);
+        }
+        if let SignalEdge::Falling = paint_edge {
+            // Only a click that did not turn into a drag paints. A drag just selects an area.
+            if self.drag_start.take() == Some(hovered) {
+                paint(&mut status, &mut level_edit);
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+        if let SignalEdge::Rising = erase_edge {
+            level_edit.place_tile(true, hovered, None);
+            level_edit.commit_edit();
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if old_selection != (status.selection.start, status.selection.end) {
+            channel.single_write(RefreshPreviewsEvent);
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use amethyst::assets::Loader;
+use amethyst::core::ecs::{Entities, Entity, Join, Read, System, WriteStorage};
+use amethyst::prelude::{Builder, World, WorldExt};
+use amethyst::ui::{Anchor, Interactable, LineMode, TtfFormat, UiImage, UiText, UiTransform};
+
+use dsf_core::levels::load_sprite_render;
+use dsf_core::resources::{AssetType, Assets};
+
+use crate::resources::{EditorStatus, LevelEdit, HOTBAR_SLOTS, RECENT_CAPACITY};
+
+const PALETTE_SLOT_PREFIX: &str = "palette_slot_";
+const HOTBAR_SLOT_PREFIX: &str = "hotbar_slot_";
+const RECENT_SLOT_PREFIX: &str = "recent_slot_";
+const PALETTE_HIGHLIGHT_ID: &str = "palette_highlight";
+
+/// The width and height of a single tile image in the panels.
+const SLOT_SIZE: f32 = 48.;
+/// The distance between the centers of two adjacent slots.
+const SLOT_SPACING: f32 = 56.;
+const PALETTE_COLUMNS: usize = 4;
+const PALETTE_WIDTH: f32 = PALETTE_COLUMNS as f32 * SLOT_SPACING;
+/// Distance between the palette and the right edge of the screen.
+const PALETTE_MARGIN: f32 = 16.;
+/// The palette starts below the menu bar.
+const PALETTE_TOP: f32 = -80.;
+const HEADER_HEIGHT: f32 = 32.;
+/// The recently used tiles are shown in the menu bar, to the right of the brush preview.
+const RECENT_LEFT: f32 = 368.;
+/// Is drawn on top of the menu bar.
+const SLOT_Z: f32 = 10.;
+
+/// Returns the id of the UI image that shows the palette entry with the given index.
+pub fn palette_slot_id(index: usize) -> String {
+    format!("{}{}", PALETTE_SLOT_PREFIX, index)
+}
+
+/// If the given id belongs to a palette slot, returns the index of its palette entry.
+pub fn palette_slot_index(id: &str) -> Option<usize> {
+    slot_index(id, PALETTE_SLOT_PREFIX)
+}
+
+fn slot_index(id: &str, prefix: &str) -> Option<usize> {
+    id.strip_prefix(prefix)?.parse().ok()
+}
+
+/// Returns a UI image that shows the given tile, or plain black for air.
+pub fn brush_image(key: &Option<String>, level_edit: &LevelEdit, assets: &Assets) -> UiImage {
+    key.as_ref()
+        .map(|key| level_edit.get_tile_def(key))
+        .and_then(|tile_def| {
+            if let AssetType::Still(sprite, sprite_nr) = tile_def.get_preview() {
+                Some(load_sprite_render(&sprite, sprite_nr, assets))
+            } else {
+                None
+            }
+        })
+        .map(UiImage::Sprite)
+        .unwrap_or(UiImage::SolidColor([0.0, 0.0, 0.0, 1.0]))
+}
+
+/// Creates the palette panel along the right side of the screen, the hotbar along the bottom and
+/// the row of recently used tiles in the menu bar.
+///
+/// The palette lists every tile, grouped under a header per category. Clicking a tile selects it.
+/// Must be called after the brush palette is set and the LevelEdit is inserted.
+pub fn init_palette_panel(world: &mut World) {
+    let font = world.read_resource::<Loader>().load(
+        "font/square.ttf",
+        TtfFormat,
+        (),
+        &world.read_resource(),
+    );
+    let (palette, images) = {
+        let status = world.read_resource::<EditorStatus>();
+        let level_edit = world.read_resource::<LevelEdit>();
+        let assets = world.read_resource::<Assets>();
+        let palette = status.brush.palette().to_vec();
+        let images = palette
+            .iter()
+            .map(|entry| brush_image(&entry.key, &level_edit, &assets))
+            .collect::<Vec<_>>();
+        (palette, images)
+    };
+
+    let mut y = PALETTE_TOP;
+    let mut column = 0;
+    let mut category = None;
+    for (index, (entry, image)) in palette.iter().zip(images).enumerate() {
+        if category != Some(entry.category) {
+            if column != 0 {
+                column = 0;
+                y -= SLOT_SPACING;
+            }
+            category = Some(entry.category);
+            world
+                .create_entity()
+                .with(UiTransform::new(
+                    format!("palette_header_{:?}", entry.category),
+                    Anchor::TopRight,
+                    Anchor::Middle,
+                    -PALETTE_MARGIN - PALETTE_WIDTH * 0.5,
+                    y - HEADER_HEIGHT * 0.5,
+                    SLOT_Z,
+                    PALETTE_WIDTH,
+                    HEADER_HEIGHT,
+                ))
+                .with(UiText::new(
+                    font.clone(),
+                    format!("{:?}", entry.category),
+                    [1., 1., 1., 1.],
+                    20.,
+                    LineMode::Single,
+                    Anchor::MiddleLeft,
+                ))
+                .build();
+            y -= HEADER_HEIGHT;
+        }
+        let x = -PALETTE_MARGIN - PALETTE_WIDTH + SLOT_SPACING * (column as f32 + 0.5);
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                palette_slot_id(index),
+                Anchor::TopRight,
+                Anchor::Middle,
+                x,
+                y - SLOT_SPACING * 0.5,
+                SLOT_Z + 1.,
+                SLOT_SIZE,
+                SLOT_SIZE,
+            ))
+            .with(image)
+            .with(Interactable)
+            .build();
+        column += 1;
+        if column == PALETTE_COLUMNS {
+            column = 0;
+            y -= SLOT_SPACING;
+        }
+    }
+    // Sits behind the selected palette entry. The PalettePanelSystem moves it around.
+    world
+        .create_entity()
+        .with(UiTransform::new(
+            PALETTE_HIGHLIGHT_ID.to_string(),
+            Anchor::TopRight,
+            Anchor::Middle,
+            0.,
+            0.,
+            SLOT_Z,
+            SLOT_SPACING,
+            SLOT_SPACING,
+        ))
+        .with(UiImage::SolidColor([1.0, 0.85, 0.2, 1.0]))
+        .build();
+
+    for slot in 0..HOTBAR_SLOTS {
+        let x = (slot as f32 - (HOTBAR_SLOTS - 1) as f32 * 0.5) * SLOT_SPACING;
+        let y = SLOT_SPACING * 0.5;
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                format!("{}{}", HOTBAR_SLOT_PREFIX, slot),
+                Anchor::BottomMiddle,
+                Anchor::Middle,
+                x,
+                y,
+                SLOT_Z,
+                SLOT_SIZE,
+                SLOT_SIZE,
+            ))
+            .with(UiImage::SolidColor([0.0, 0.0, 0.0, 1.0]))
+            .build();
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                format!("hotbar_number_{}", slot),
+                Anchor::BottomMiddle,
+                Anchor::Middle,
+                x - SLOT_SIZE * 0.3,
+                y + SLOT_SIZE * 0.3,
+                SLOT_Z + 1.,
+                SLOT_SIZE * 0.4,
+                SLOT_SIZE * 0.4,
+            ))
+            .with(UiText::new(
+                font.clone(),
+                (slot + 1).to_string(),
+                [1., 1., 1., 1.],
+                16.,
+                LineMode::Single,
+                Anchor::Middle,
+            ))
+            .build();
+    }
+
+    for index in 0..RECENT_CAPACITY {
+        world
+            .create_entity()
+            .with(UiTransform::new(
+                format!("{}{}", RECENT_SLOT_PREFIX, index),
+                Anchor::TopLeft,
+                Anchor::Middle,
+                RECENT_LEFT + index as f32 * SLOT_SPACING,
+                -32.,
+                SLOT_Z,
+                SLOT_SIZE,
+                SLOT_SIZE,
+            ))
+            .with(UiImage::SolidColor([0.0, 0.0, 0.0, 0.0]))
+            .build();
+    }
+}
+
+/// Keeps the palette panel, the hotbar and the row of recently used tiles up to date with the
+/// brush.
+///
+/// The panels are only updated when what they show changes, or when they were recreated.
+#[derive(Default)]
+pub struct PalettePanelSystem {
+    /// What the panels were updated to show the last time.
+    shown: Option<ShownPanels>,
+}
+
+/// Everything that the palette panel, hotbar and recently used tiles depend on.
+#[derive(Debug, PartialEq)]
+struct ShownPanels {
+    /// The panels are recreated every time the editor is set up. They can be recognised by the
+    /// highlight entity, which is recreated along with them.
+    highlight: Entity,
+    palette_index: usize,
+    hotbar: Vec<Option<String>>,
+    recent: Vec<Option<String>>,
+}
+
+impl<'s> System<'s> for PalettePanelSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiImage>,
+        Read<'s, EditorStatus>,
+        Read<'s, LevelEdit>,
+        Read<'s, Assets>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut ui_transforms, mut ui_images, status, level_edit, assets): Self::SystemData,
+    ) {
+        let highlight = (&entities, &ui_transforms)
+            .join()
+            .find(|(_, transform)| transform.id == PALETTE_HIGHLIGHT_ID)
+            .map(|(entity, _)| entity);
+        let highlight = match highlight {
+            Some(highlight) => highlight,
+            None => return,
+        };
+        let to_show = ShownPanels {
+            highlight,
+            palette_index: status.brush.palette_index(),
+            hotbar: status.brush.hotbar().to_vec(),
+            recent: status.brush.recent().to_vec(),
+        };
+        if self.shown.as_ref() == Some(&to_show) {
+            return;
+        }
+        self.shown = Some(to_show);
+
+        let selected_id = palette_slot_id(status.brush.palette_index());
+        let selected_pos = (&ui_transforms)
+            .join()
+            .find(|transform| transform.id == selected_id)
+            .map(|transform| (transform.local_x, transform.local_y));
+        if let Some((x, y)) = selected_pos {
+            if let Some(transform) = ui_transforms.get_mut(highlight) {
+                transform.local_x = x;
+                transform.local_y = y;
+            }
+        }
+        for (transform, image) in (&ui_transforms, &mut ui_images).join() {
+            if let Some(slot) = slot_index(&transform.id, HOTBAR_SLOT_PREFIX) {
+                if let Some(key) = status.brush.hotbar().get(slot) {
+                    *image = brush_image(key, &level_edit, &assets);
+                }
+            } else if let Some(index) = slot_index(&transform.id, RECENT_SLOT_PREFIX) {
+                *image = match status.brush.recent().get(index) {
+                    Some(key) => brush_image(key, &level_edit, &assets),
+                    None => UiImage::SolidColor([0.0, 0.0, 0.0, 0.0]),
+                };
+            }
+        }
+    }
+}
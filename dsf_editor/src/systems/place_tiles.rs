@@ -2,12 +2,14 @@ use amethyst::core::ecs::{Read, System, Write};
 use amethyst::input::{InputHandler, StringBindings};
 use amethyst::shrev::EventChannel;
 
-use dsf_core::resources::{SignalEdge, SignalEdgeDetector};
+use dsf_core::components::Pos;
+use dsf_core::resources::{SignalEdge, SignalEdgeDetector, Tile};
 
 use crate::resources::{Blueprint, EditorStatus, LevelEdit};
 use crate::systems::RefreshPreviewsEvent;
 
-/// Responsible for placing and removing tiles based on player input.
+/// Responsible for placing and removing tiles based on player input, for copying, cutting,
+/// transforming and pasting selections, and for undoing and redoing those changes.
 pub struct PlaceTilesSystem;
 
 impl<'s> System<'s> for PlaceTilesSystem {
@@ -16,20 +18,54 @@ impl<'s> System<'s> for PlaceTilesSystem {
         Write<'s, EventChannel<RefreshPreviewsEvent>>,
         Write<'s, SignalEdgeDetector>,
         Read<'s, InputHandler<StringBindings>>,
-        Read<'s, EditorStatus>,
+        Write<'s, EditorStatus>,
         Write<'s, LevelEdit>,
     );
 
-    fn run(&mut self, (mut channel, mut sed, input, status, mut level_edit): Self::SystemData) {
+    fn run(&mut self, (mut channel, mut sed, input, mut status, mut level_edit): Self::SystemData) {
         if let SignalEdge::Rising = sed.edge("place_blocks", &input) {
-            let blueprint = Blueprint::from_placing_tiles(&status, &level_edit);
-            let lower_bounds = status.selection.lower_bounds();
-            blueprint.tiles.iter().for_each(|(relative_pos, tile)| {
-                level_edit.place_tile(
-                    status.force_place,
-                    lower_bounds + *relative_pos,
-                    Some(tile.clone()),
-                );
+            paint(&mut status, &mut level_edit);
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("copy", &input) {
+            copy_selection(&mut status, &level_edit);
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("cut", &input) {
+            let origin = blueprint_origin(&status);
+            let blueprint = copy_selection(&mut status, &level_edit);
+            blueprint
+                .tiles
+                .iter()
+                .filter(|(_, tile)| matches!(tile, Tile::TileDefKey(_)))
+                .for_each(|(relative_pos, _)| {
+                    level_edit.place_tile(true, origin + *relative_pos, None);
+                });
+            level_edit.commit_edit();
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("paste", &input) {
+            if let Some(clipboard) = status.clipboard.clone() {
+                status.pasting = true;
+                place_blueprint(&mut level_edit, &status, &clipboard);
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+        if let SignalEdge::Rising = sed.edge("flip_horizontally", &input) {
+            transform_clipboard(&mut status, |blueprint| {
+                blueprint.flipped_horizontally(&level_edit.tile_map.tile_defs)
+            });
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("flip_vertically", &input) {
+            transform_clipboard(&mut status, |blueprint| {
+                blueprint.flipped_vertically(&level_edit.tile_map.tile_defs)
+            });
+            channel.single_write(RefreshPreviewsEvent);
+        }
+        if let SignalEdge::Rising = sed.edge("rotate", &input) {
+            transform_clipboard(&mut status, |blueprint| {
+                blueprint.rotated_clockwise(&level_edit.tile_map.tile_defs)
             });
             channel.single_write(RefreshPreviewsEvent);
         }
@@ -41,7 +77,82 @@ impl<'s> System<'s> for PlaceTilesSystem {
                     level_edit.place_tile(true, lower_bounds.append_xy(x, y), None);
                 });
             });
+            level_edit.commit_edit();
             channel.single_write(RefreshPreviewsEvent);
         }
+        if let SignalEdge::Rising = sed.edge("undo", &input) {
+            if level_edit.undo() {
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+        if let SignalEdge::Rising = sed.edge("redo", &input) {
+            if level_edit.redo() {
+                channel.single_write(RefreshPreviewsEvent);
+            }
+        }
+    }
+}
+
+/// Place the tile on the brush, or the clipboard if the editor is pasting, as a single undoable
+/// step.
+pub(crate) fn paint(status: &mut EditorStatus, level_edit: &mut LevelEdit) {
+    if !status.pasting {
+        status.brush.mark_used();
+    }
+    let blueprint = Blueprint::from_status(status, level_edit);
+    place_blueprint(level_edit, status, &blueprint);
+}
+
+/// The position in the world where the lower-left corner of a blueprint will be placed.
+fn blueprint_origin(status: &EditorStatus) -> Pos {
+    status.selection.lower_bounds()
+}
+
+/// Place all tiles in the blueprint as a single undoable step.
+fn place_blueprint(level_edit: &mut LevelEdit, status: &EditorStatus, blueprint: &Blueprint) {
+    let origin = blueprint_origin(status);
+    blueprint.tiles.iter().for_each(|(relative_pos, tile)| {
+        level_edit.place_tile(
+            status.forces_placement(),
+            origin + *relative_pos,
+            Some(tile.clone()),
+        );
+    });
+    level_edit.commit_edit();
+}
+
+/// Copy the current selection to the clipboard and enter paste mode. The selection collapses onto
+/// the cursor, so the previews show where the clipboard would be pasted.
+/// Returns the copied blueprint.
+fn copy_selection(status: &mut EditorStatus, level_edit: &LevelEdit) -> Blueprint {
+    let blueprint = Blueprint::from_selection(status, level_edit);
+    status.clipboard = Some(blueprint.clone());
+    status.pasting = true;
+    status.selection.start = status.selection.end;
+    blueprint
+}
+
+/// Replace the clipboard with a transformed version of itself and enter paste mode, so the
+/// previews show the result. Does nothing if the clipboard is empty.
+/// Tiles that no longer fit after the transformation are reported to the designer.
+fn transform_clipboard<F>(status: &mut EditorStatus, transform: F)
+where
+    F: Fn(&Blueprint) -> (Blueprint, Vec<String>),
+{
+    if let Some(clipboard) = &status.clipboard {
+        let (transformed, dropped) = transform(clipboard);
+        if !dropped.is_empty() {
+            warn!(
+                "Tiles do not fit after transforming the clipboard: {:?}",
+                dropped
+            );
+            status.message = format!(
+                "Left out {} overlapping tile(s): {}",
+                dropped.len(),
+                dropped.join(", ")
+            );
+        }
+        status.clipboard = Some(transformed);
+        status.pasting = true;
     }
 }
@@ -52,7 +52,7 @@ impl<'s> System<'s> for RefreshPreviewsSystem {
         if !at_least_one_event {
             return;
         }
-        let blueprint = Blueprint::from_placing_tiles(&status, &level_edit);
+        let blueprint = Blueprint::from_status(&status, &level_edit);
         let lower_bounds = status.selection.lower_bounds();
         let blueprint_dry_run =
             blueprint
@@ -60,7 +60,7 @@ impl<'s> System<'s> for RefreshPreviewsSystem {
                 .iter()
                 .fold(PlaceTileDryRun::default(), |accumulator, (pos, tile)| {
                     let place_tile_dry_run = level_edit.check_place_tile(
-                        status.force_place,
+                        status.forces_placement(),
                         lower_bounds + *pos,
                         Some(tile.clone()),
                     );
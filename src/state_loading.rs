@@ -3,7 +3,7 @@ use amethyst::ui::UiCreator;
 use amethyst::ui::UiLoader;
 use dsf_core::resources::{
     load_audio_settings, load_debug_settings, Assets, AudioSettings, MovementConfig, Music,
-    UiHandles, UserCache,
+    SaveGame, UiHandles, UserCache,
 };
 
 use amethyst::{
@@ -17,7 +17,7 @@ use dsf_precompile::MyPrefabData;
 
 use crate::state_main_menu::MainMenuState;
 use amethyst::audio::{AudioSink, Mp3Format, WavFormat};
-use dsf_core::utility::files::{get_config_dir, get_user_cache_file};
+use dsf_core::utility::files::{get_config_dir, get_save_game_file, get_user_cache_file};
 use dsf_core::utility::loading_config::LoadingConfig;
 use dsf_editor::resources::EditorConfig;
 
@@ -201,4 +201,15 @@ fn load_configs(world: &mut World) {
     } else {
         UserCache::default()
     });
+    world.insert(if get_save_game_file().is_file() {
+        SaveGame::load(get_save_game_file()).unwrap_or_else(|error| {
+            error!(
+                "Failed to load save game! Falling back to default. Error: {:?}",
+                error
+            );
+            SaveGame::default()
+        })
+    } else {
+        SaveGame::default()
+    });
 }